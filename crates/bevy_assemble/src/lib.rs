@@ -2,6 +2,7 @@ use std::any::TypeId;
 use std::collections::{HashMap, HashSet};
 
 use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::entity::MapEntities;
 use bevy_ecs::prelude::ReflectComponent;
 use bevy_ecs::prelude::*;
 use bevy_reflect::Reflect;
@@ -34,15 +35,66 @@ pub struct SaveSuccess {
 }
 
 /// current stage of request for joint from increasing context.
-#[derive(Debug, Reflect, Clone)]
+#[derive(Debug, Reflect, Clone, MapEntities)]
 pub enum JointRequestStage {
     Name(String),
-    Entity(Entity),
+    Entity(#[entities] Entity),
 }
 
 /// Request for a joint. Split into stages depending on available info on joint at time of initialization. Eventually elevated to [`JointFlag`]
 #[derive(Component, Debug, Reflect, Clone)]
+#[reflect(Component)]
 pub struct JointRequest {
+    #[entities]
     pub stage: JointRequestStage,
     pub joint: JointInfo,
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::EntityHashMap;
+    use bevy_scene::{DynamicSceneBuilder, ron, serde::SceneDeserializer};
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+
+    #[test]
+    fn joint_request_entity_is_remapped_on_load() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<JointRequest>();
+        let parent = world.spawn_empty().id();
+        let child = world
+            .spawn(JointRequest {
+                stage: JointRequestStage::Entity(parent),
+                joint: JointInfo::default(),
+            })
+            .id();
+
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let save = DynamicSceneBuilder::from_world(&world)
+            .extract_entities([parent, child].into_iter())
+            .build()
+            .serialize(&registry.read())
+            .unwrap();
+        let mut deserializer = ron::de::Deserializer::from_str(&save).unwrap();
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .unwrap();
+
+        let mut entity_map = EntityHashMap::default();
+        scene.write_to_world(&mut world, &mut entity_map).unwrap();
+        let (loaded_parent, loaded_child) = (entity_map[&parent], entity_map[&child]);
+        assert_ne!(loaded_parent, parent);
+        let JointRequestStage::Entity(e) = world.get::<JointRequest>(loaded_child).unwrap().stage
+        else {
+            panic!("stage changed on load");
+        };
+        assert_eq!(e, loaded_parent);
+    }
+}
@@ -84,25 +84,25 @@ impl From<&Mesh3dFlag> for GeometryWrapper {
                     MeshPrefab::Cuboid(cuboid) => Self(Geometry::Box {
                         size: urdf_rs::Vec3(cuboid.size().to_array().map(|n| n as f64)),
                     }),
-                    MeshPrefab::Cylinder(cylinder) => Self(Geometry::Cylinder {
+                    MeshPrefab::Cylinder(cylinder, _) => Self(Geometry::Cylinder {
                         radius: cylinder.radius as f64,
                         length: (cylinder.half_height * 2.0) as f64,
                     }),
-                    MeshPrefab::Capsule(capsule3d) => Self(Geometry::Capsule {
+                    MeshPrefab::Capsule(capsule3d, _) => Self(Geometry::Capsule {
                         radius: capsule3d.radius as f64,
                         length: (capsule3d.half_length * 2.0) as f64,
                     }),
-                    MeshPrefab::Sphere(sphere) => Self(Geometry::Sphere {
+                    MeshPrefab::Sphere(sphere, _) => Self(Geometry::Sphere {
                         radius: sphere.radius as f64,
                     }),
-                    MeshPrefab::Cone(_cone) => {
+                    MeshPrefab::Cone(..) => {
                         warn!("Cones not supported by urdf-rs. Using fallback primitive.");
                         Self(FALLBACK_GEOMETRY)
                     }
-                    MeshPrefab::Unimplemented(mesh) => {
+                    unsupported => {
                         warn!(
-                            "Unimplemented mesh prefab {:#?}. Using fallback primitive",
-                            mesh
+                            "{:#?} not supported by urdf-rs. Using fallback primitive",
+                            unsupported
                         );
                         Self(FALLBACK_GEOMETRY)
                     }
@@ -136,7 +136,7 @@ impl From<GeometryWrapper>
             urdf_rs::Geometry::Cylinder { radius, length } => {
                 //TODO: double check that this is correct
                 Resolve::One(Mesh3dFlag::Pure(
-                    MeshPrefab::Cylinder(Cylinder {
+                    MeshPrefab::from(Cylinder {
                         radius: radius as f32,
                         half_height: length as f32,
                     })
@@ -146,7 +146,7 @@ impl From<GeometryWrapper>
             urdf_rs::Geometry::Capsule { radius, length } => {
                 //TODO: double check that this is correct
                 Resolve::One(Mesh3dFlag::Pure(
-                    MeshPrefab::Capsule(Capsule3d {
+                    MeshPrefab::from(Capsule3d {
                         radius: radius as f32,
                         half_length: length as f32,
                     })
@@ -154,7 +154,7 @@ impl From<GeometryWrapper>
                 ))
             }
             urdf_rs::Geometry::Sphere { radius } => Resolve::One(Mesh3dFlag::Pure(
-                MeshPrefab::Sphere(Sphere {
+                MeshPrefab::from(Sphere {
                     radius: radius as f32,
                 })
                 .into(),
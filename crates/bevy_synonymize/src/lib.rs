@@ -1,4 +1,9 @@
 //! library for syncing synonomous components with eachother.
+//!
+//! render dependent synonyms([`synonyms::mesh`], [`synonyms::material`]) are gated behind the `mesh`/`material` features(both enabled by `render`).
+//! 2d synonyms([`synonyms::color_material`], [`plugins::Synonymize2dPlugin`]) are gated behind `render_2d`. [`synonyms::mesh2d`]
+//! only needs `mesh`, as 3d extrusions are built from its prefabs.
+//! Disable default features for headless builds.
 
 use std::ops::Deref;
 
@@ -14,9 +19,13 @@ mod systems;
 pub mod traits;
 pub mod plugins;
 pub mod synonyms;
+pub mod persistent;
 
 pub mod prelude {
-    pub use crate::{resources::*, synonyms::*, traits::*};
+    pub use crate::{persistent::*, resources::*, traits::*};
+    // empty without any of the render features.
+    #[cfg(any(feature = "mesh", feature = "material"))]
+    pub use crate::synonyms::*;
 }
 
 #[doc = "hidden"]
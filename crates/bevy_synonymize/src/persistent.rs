@@ -0,0 +1,176 @@
+//! stable ids for entities, for referring to them across sessions and from outside of the world(level DBs, scripts, urdf parents, ...).
+//!
+//! [`Entity`] ids change every time a save is loaded. A [`PersistentId`] is saved along with its entity and stays the same.
+//! Look entities up by id with [`PersistentIds`].
+
+use bevy_ecs::{
+    component::HookContext,
+    entity::{EntityMapper, MapEntities},
+    prelude::*,
+    world::DeferredWorld,
+};
+use bevy_log::warn;
+use bevy_reflect::Reflect;
+use uuid::Uuid;
+
+use crate::resources::PersistentIds;
+
+/// stable id of an entity. Kept in sync with [`PersistentIds`].
+///
+/// Insert a new id to change it rather than editing it in place, or [`PersistentIds`] won't see the change.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[reflect(Component, Debug, PartialEq, Hash)]
+#[component(on_insert = on_insert_id, on_replace = on_replace_id)]
+pub struct PersistentId(pub Uuid);
+
+impl PersistentId {
+    /// a new random id.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for PersistentId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn on_insert_id(mut world: DeferredWorld, context: HookContext) {
+    let Some(id) = world.get::<PersistentId>(context.entity).copied() else {
+        return;
+    };
+    let Some(mut ids) = world.get_resource_mut::<PersistentIds>() else {
+        return;
+    };
+    if let Some(existing) = ids.insert(id, context.entity)
+        && existing != context.entity
+    {
+        warn!(
+            "{:#} was given persistent id {:#?}, which {:#} already has. Lookups by it now find {:#}",
+            context.entity, id, existing, context.entity
+        );
+    }
+}
+
+fn on_replace_id(mut world: DeferredWorld, context: HookContext) {
+    let Some(id) = world.get::<PersistentId>(context.entity).copied() else {
+        return;
+    };
+    if let Some(mut ids) = world.get_resource_mut::<PersistentIds>() {
+        ids.remove(id, context.entity);
+    }
+}
+
+/// reference to an entity by [`Entity`] and [`PersistentId`], for synonyms that refer to entities that may not be in
+/// the same save(or loaded yet). See [`PersistentRefs`](crate::traits::PersistentRefs).
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct PersistentRef {
+    pub entity: Entity,
+    pub id: Option<PersistentId>,
+}
+
+impl PersistentRef {
+    pub fn new(entity: Entity) -> Self {
+        Self { entity, id: None }
+    }
+
+    /// fills in the id of the entity, or if the entity has none(e.g: it wasn't in the save this was loaded from),
+    /// points at the entity with the id.
+    pub fn resolve(&mut self, ids: &PersistentIds) {
+        match ids.id(self.entity) {
+            Some(id) => self.id = Some(id),
+            None => {
+                if let Some(entity) = self.id.and_then(|n| ids.entity(n)) {
+                    self.entity = entity;
+                }
+            }
+        }
+    }
+}
+
+impl From<Entity> for PersistentRef {
+    fn from(entity: Entity) -> Self {
+        Self::new(entity)
+    }
+}
+
+impl MapEntities for PersistentRef {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        self.entity = entity_mapper.get_mapped(self.entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use bevy_log::{
+        tracing::{self, Event, Level, Subscriber},
+        tracing_subscriber::{
+            Layer,
+            layer::{Context, SubscriberExt},
+            registry,
+        },
+    };
+
+    use super::*;
+
+    /// counts warnings logged while it is the default subscriber.
+    struct CountWarnings(Arc<AtomicUsize>);
+
+    impl<S: Subscriber> Layer<S> for CountWarnings {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == Level::WARN {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_id_points_at_new_entity() {
+        let mut world = World::new();
+        world.init_resource::<PersistentIds>();
+        let id = PersistentId::new();
+        let first = world.spawn(id).id();
+        assert_eq!(world.resource::<PersistentIds>().entity(id), Some(first));
+
+        let warnings = Arc::new(AtomicUsize::new(0));
+        let subscriber = registry().with(CountWarnings(warnings.clone()));
+        let second = tracing::subscriber::with_default(subscriber, || world.spawn(id).id());
+        assert_eq!(warnings.load(Ordering::Relaxed), 1);
+
+        let ids = world.resource::<PersistentIds>();
+        assert_eq!(ids.entity(id), Some(second));
+        assert_eq!(ids.id(second), Some(id));
+        assert_eq!(ids.id(first), None);
+        assert_eq!(ids.len(), 1);
+
+        // removing the stale copy leaves the lookup alone.
+        world.entity_mut(first).remove::<PersistentId>();
+        assert_eq!(world.resource::<PersistentIds>().entity(id), Some(second));
+    }
+
+    #[test]
+    fn resolves_refs() {
+        let mut world = World::new();
+        world.init_resource::<PersistentIds>();
+        let id = PersistentId::new();
+        let target = world.spawn(id).id();
+
+        // fills in the id of an entity that has one.
+        let mut persistent_ref = PersistentRef::new(target);
+        persistent_ref.resolve(world.resource::<PersistentIds>());
+        assert_eq!(persistent_ref.id, Some(id));
+
+        // the entity is gone(e.g: unloaded, then loaded back as another entity). Follows the id.
+        world.despawn(target);
+        let loaded = world.spawn(id).id();
+        persistent_ref.resolve(world.resource::<PersistentIds>());
+        assert_eq!(persistent_ref.entity, loaded);
+        assert_eq!(persistent_ref.id, Some(id));
+    }
+}
@@ -4,9 +4,16 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_asset::prelude::*;
 use bevy_log::warn;
+#[cfg(feature = "render")]
 use bevy_pbr::StandardMaterial;
-use crate::{prelude::{material::{Material3dFlag, MeshMaterial3dRepr}, mesh::Mesh3dFlag, InitializedSynonyms}, traits::{AssetSynonymTarget, SynonymPaths}};
-use crate::{systems::{desynonymize_assset, desynonymize, synonymize, try_synonymize_asset}, traits::{AssetState, ComponentSynonym}};
+#[cfg(feature = "render")]
+use crate::prelude::{material::MeshMaterial3dRepr, mesh::Mesh3dRepr};
+#[cfg(feature = "render_2d")]
+use bevy_sprite::ColorMaterial;
+#[cfg(feature = "render_2d")]
+use crate::prelude::{color_material::MeshMaterial2dRepr, mesh2d::Mesh2dRepr};
+use crate::{persistent::PersistentId, prelude::{InitializedSynonyms, PersistentIds, SynonymTargets}, traits::{AssetSynonymTarget, PersistentRefs, ReflectAssetPath, SynonymPaths}};
+use crate::{systems::{desynonymize_assset, desynonymize, resolve_persistent_refs, synonymize, try_synonymize_asset}, traits::{AssetState, ComponentSynonym}};
 
 
 
@@ -25,41 +32,34 @@ impl<T: ComponentSynonym> Default for SynonymizeComponent<T> {
 
 impl<T: ComponentSynonym> Plugin for SynonymizeComponent<T> {
     fn build(&self, app: &mut App) {
-        //TODO: Move this to new crate
-        //skip_serializing::<T::SynonymTarget>(app);
-        app.world_mut();
-        // .register_component_hooks::<T>().on_insert(|mut world, e, id| {
-        //         let comp = {
-        //             match world.entity(e).get::<T>() {
-        //                 Some(val) => val,
-        //                 None => {
-        //                     warn!("could not get {:#?} on: {:#}", type_name::<Self>(), e);
-        //                     return
-        //                 },
-        //             }
-        //         };
-        //         let target = T::SynonymTarget::from(&comp);
-        //         world.commands().entity(e).insert(target);
-        //     });
-
-        app.register_type::<T>().add_systems(
-            PreUpdate,
-            (synonymize::<T>, desynonymize::<T>).chain(),
-        );
+        register_synonym_target::<T::SynonymTarget, T>(app);
+
+        app.register_type::<T>()
+            .register_type_data::<T, ReflectComponent>()
+            .add_systems(
+                PreUpdate,
+                (synonymize::<T>, desynonymize::<T>).chain(),
+            );
     }
 }
 
 
 /// plugin for converting between synonymous asset component newtypes.
-#[derive(Default)]
 pub struct SynonymizeAsset<T: AssetSynonymTarget + 'static> {
     thing: PhantomData<fn() -> T>,
 }
 
+impl<T: AssetSynonymTarget> Default for SynonymizeAsset<T> {
+    fn default() -> Self {
+        Self {
+            thing: Default::default(),
+        }
+    }
+}
+
 impl<T: AssetSynonymTarget> Plugin for SynonymizeAsset<T> {
     fn build(&self, app: &mut App) {
-        //TODO: Move this to new crate
-        //skip_serializing::<T::SynonymTarget>(app);
+        register_synonym_target::<T::Target, T::Synonym>(app);
 
         let synonym_id = TypeId::of::<T::Synonym>();
         let initializing_repr = type_name::<T>().to_string();
@@ -80,6 +80,8 @@ impl<T: AssetSynonymTarget> Plugin for SynonymizeAsset<T> {
         );
 
         app.register_type::<T::Synonym>()
+            .register_type_data::<T::Synonym, ReflectComponent>()
+            .register_type_data::<T::Synonym, ReflectAssetPath>()
             .world_mut()
             .register_component_hooks::<T::Synonym>()
             .on_add(|mut world, hook_context| {
@@ -114,7 +116,7 @@ impl<T: AssetSynonymTarget> Plugin for SynonymizeAsset<T> {
                                 );
                                 return;
                             };
-                            let asset = T::from_synonym(pure);
+                            let asset = T::from_synonym(pure, assets);
                             assets.add(asset)
                         }
                     }
@@ -129,13 +131,64 @@ impl<T: AssetSynonymTarget> Plugin for SynonymizeAsset<T> {
     }
 }
 
+/// keeps [`PersistentRef`]s in `T` pointed at the right entities. See [`PersistentRefs`].
+///
+/// [`PersistentRef`]: crate::persistent::PersistentRef
+pub struct ResolvePersistentRefs<T: PersistentRefs> {
+    thing: PhantomData<fn() -> T>,
+}
+
+impl<T: PersistentRefs> Default for ResolvePersistentRefs<T> {
+    fn default() -> Self {
+        Self {
+            thing: Default::default(),
+        }
+    }
+}
+
+impl<T: PersistentRefs> Plugin for ResolvePersistentRefs<T> {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PersistentId>()
+            .init_resource::<PersistentIds>()
+            .add_systems(PreUpdate, resolve_persistent_refs::<T>);
+    }
+}
+
+/// marks `Target` as serialized through `Synonym`, so savers skip `Target` instead of failing on/duplicating it.
+fn register_synonym_target<Target: 'static, Synonym: 'static>(app: &mut App) {
+    app.world_mut()
+        .get_resource_or_init::<SynonymTargets>()
+        .insert(TypeId::of::<Target>(), TypeId::of::<Synonym>());
+}
+
 /// base Synonymizations for this library.
+///
+/// Requires the `render` feature.
+#[cfg(feature = "render")]
 pub struct SynonymizeBasePlugin;
 
+#[cfg(feature = "render")]
 impl Plugin for SynonymizeBasePlugin {
     fn build(&self, app: &mut App) {
         app
+        .add_plugins(SynonymizeAsset::<Mesh3dRepr>::default())
         .add_plugins(SynonymizeAsset::<MeshMaterial3dRepr<StandardMaterial>>::default())
         ;
     }
-}
\ No newline at end of file
+}
+
+/// 2d Synonymizations for this library.
+///
+/// Requires the `render_2d` feature.
+#[cfg(feature = "render_2d")]
+pub struct Synonymize2dPlugin;
+
+#[cfg(feature = "render_2d")]
+impl Plugin for Synonymize2dPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .add_plugins(SynonymizeAsset::<Mesh2dRepr>::default())
+        .add_plugins(SynonymizeAsset::<MeshMaterial2dRepr<ColorMaterial>>::default())
+        ;
+    }
+}
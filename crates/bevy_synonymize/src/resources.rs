@@ -1,9 +1,68 @@
 use std::{any::TypeId, collections::HashMap};
 
 use bevy_derive::{Deref, DerefMut};
-use bevy_ecs::prelude::*;
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+
+use crate::persistent::PersistentId;
 
 #[derive(Resource, Default, Deref, DerefMut)]
 pub struct InitializedSynonyms{
     synonyms: HashMap<TypeId, String>
-}
\ No newline at end of file
+}
+/// synonym targets(key) and the synonym they are serialized as instead(value).
+///
+/// Filled in by [`SynonymizeComponent`]/[`SynonymizeAsset`] so savers know to skip targets.
+///
+/// [`SynonymizeComponent`]: crate::plugins::SynonymizeComponent
+/// [`SynonymizeAsset`]: crate::plugins::SynonymizeAsset
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct SynonymTargets(pub HashMap<TypeId, TypeId>);
+
+/// [`PersistentId`] <-> entity lookup. Kept up to date by [`PersistentId`]'s hooks.
+#[derive(Resource, Default, Debug)]
+pub struct PersistentIds {
+    entities: HashMap<PersistentId, Entity>,
+    ids: EntityHashMap<PersistentId>,
+}
+
+impl PersistentIds {
+    pub fn entity(&self, id: PersistentId) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+
+    pub fn id(&self, entity: Entity) -> Option<PersistentId> {
+        self.ids.get(&entity).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (PersistentId, Entity)> + '_ {
+        self.entities.iter().map(|(id, e)| (*id, *e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// returns the entity that had `id` before.
+    pub(crate) fn insert(&mut self, id: PersistentId, entity: Entity) -> Option<Entity> {
+        let existing = self.entities.insert(id, entity);
+        if let Some(existing) = existing {
+            self.ids.remove(&existing);
+        }
+        self.ids.insert(entity, id);
+        existing
+    }
+
+    /// forgets `id`, if it still belongs to `entity`.
+    pub(crate) fn remove(&mut self, id: PersistentId, entity: Entity) {
+        if self.entities.get(&id) == Some(&entity) {
+            self.entities.remove(&id);
+        }
+        if self.ids.get(&entity) == Some(&id) {
+            self.ids.remove(&entity);
+        }
+    }
+}
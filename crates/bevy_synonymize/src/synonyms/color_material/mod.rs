@@ -0,0 +1,99 @@
+use bevy_asset::AssetServer;
+use bevy_color::prelude::*;
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::component::Component;
+use bevy_ecs::prelude::ReflectComponent;
+use bevy_log::warn;
+use bevy_reflect::prelude::*;
+use bevy_sprite::{AlphaMode2d, ColorMaterial, Material2d, MeshMaterial2d};
+use bevy_utils::prelude::*;
+use bytemuck::TransparentWrapper;
+use derive_more::derive::From;
+
+use crate::traits::{AssetState, AssetSynonymTarget, SelfPath, SelfPure, SynonymPaths, SynonymPure};
+
+/// serializable wrapper for 2d color materials
+#[derive(Component, Reflect, Clone, PartialEq, From)]
+#[reflect(Component)]
+pub enum ColorMaterialFlag {
+    Pure(ColorMaterialWrapper),
+    Path(String),
+}
+
+impl SynonymPaths for ColorMaterialFlag {
+    type Pure = ColorMaterialWrapper;
+
+    type Path = String;
+
+    fn asset_state(&self) -> AssetState<SelfPure<Self>, SelfPath<Self>> {
+        match self {
+            ColorMaterialFlag::Pure(material_wrapper) => AssetState::Pure(material_wrapper),
+            ColorMaterialFlag::Path(path) => AssetState::Path(path),
+        }
+    }
+}
+
+#[derive(Clone, From, PartialEq, Reflect)]
+pub enum ColorMaterialWrapper {
+    /// a plain color, with the rest of the material left default.
+    Color(Color),
+    Material {
+        color: Color,
+        alpha_mode: AlphaMode2d,
+        /// asset path of the texture.
+        texture: Option<String>,
+    },
+}
+
+#[derive(From, Clone, Deref, DerefMut, Default, TransparentWrapper)]
+#[repr(transparent)]
+pub struct MeshMaterial2dRepr<T: Material2d>(MeshMaterial2d<T>);
+
+impl AssetSynonymTarget for MeshMaterial2dRepr<ColorMaterial> {
+    type Synonym = ColorMaterialFlag;
+    type AssetType = ColorMaterial;
+
+    fn from_synonym(value: &SynonymPure<Self>, asset_server: &AssetServer) -> Self::AssetType {
+        match value {
+            ColorMaterialWrapper::Color(color) => Self::AssetType {
+                color: *color,
+                ..default()
+            },
+            ColorMaterialWrapper::Material {
+                color,
+                alpha_mode,
+                texture,
+            } => Self::AssetType {
+                color: *color,
+                alpha_mode: *alpha_mode,
+                texture: texture.as_ref().map(|path| asset_server.load(path.clone())),
+                ..default()
+            },
+        }
+    }
+
+    fn from_asset(value: &Self::AssetType) -> SynonymPure<Self> {
+        // textures are only saved by path. Ones built at runtime have none to save.
+        let texture = value.texture.as_ref().and_then(|texture| {
+            let path = texture.path().map(|path| path.to_string());
+            if path.is_none() {
+                warn!("color material texture {:?} has no asset path. Leaving it out", texture.id());
+            }
+            path
+        });
+        if texture.is_none() && value.alpha_mode == ColorMaterial::default().alpha_mode {
+            return SynonymPure::<Self>::Color(value.color);
+        }
+        SynonymPure::<Self>::Material {
+            color: value.color,
+            alpha_mode: value.alpha_mode,
+            texture,
+        }
+    }
+}
+
+impl Default for ColorMaterialFlag {
+    fn default() -> Self {
+        ColorMaterialFlag::Pure(Color::default().into())
+    }
+}
@@ -1,4 +1,4 @@
-use bevy_asset::Asset;
+use bevy_asset::{Asset, AssetServer};
 use bevy_color::prelude::*;
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::component::Component;
@@ -45,7 +45,7 @@ impl AssetSynonymTarget for MeshMaterial3dRepr<StandardMaterial> {
     type Synonym = Material3dFlag;
     type AssetType = StandardMaterial;
 
-    fn from_synonym(value: &SynonymPure<Self>) -> Self::AssetType {
+    fn from_synonym(value: &SynonymPure<Self>, _asset_server: &AssetServer) -> Self::AssetType {
         match value {
             MaterialWrapper::Color(color) => Self::AssetType {
                 base_color: *color,
@@ -3,34 +3,20 @@ use bytemuck::TransparentWrapper;
 use derive_more::derive::From;
 use crate::traits::*;
 
+use bevy_asset::AssetServer;
 use bevy_ecs::prelude::*;
 use bevy_math::prelude::*;
 use bevy_reflect::prelude::*;
 use bevy_render::prelude::*;
 
-/// bevy prefab meshes
-#[derive(Reflect, Clone, Copy, Debug, PartialEq, From)]
-pub enum MeshPrefab {
-    Cuboid(Cuboid),
-    Cylinder(Cylinder),
-    Capsule(Capsule3d),
-    Sphere(Sphere),
-    Cone(Cone),
-    /// Fallback for unimplemented shapes. Should lead to fallback variant mesh.
-    Unimplemented(&'static str),
-}
+mod prefab;
+
+pub use prefab::*;
 
 impl From<&MeshWrapper> for Mesh {
     fn from(value: &MeshWrapper) -> Self {
         match value {
-            MeshWrapper::Prefab(mesh_prefab) => match *mesh_prefab {
-                MeshPrefab::Cuboid(cuboid) => cuboid.into(),
-                MeshPrefab::Cylinder(cylinder) => cylinder.into(),
-                MeshPrefab::Capsule(capsule3d) => capsule3d.into(),
-                MeshPrefab::Sphere(sphere) => sphere.into(),
-                MeshPrefab::Cone(cone) => cone.into(),
-                MeshPrefab::Unimplemented(_kind) => FALLBACK_MESH.into(),
-            },
+            MeshWrapper::Prefab(mesh_prefab) => mesh_prefab.into(),
             MeshWrapper::Procedural(mesh) => mesh.clone(),
         }
     }
@@ -43,12 +29,6 @@ impl From<&Mesh> for MeshWrapper {
     }
 }
 
-impl Default for MeshPrefab {
-    fn default() -> Self {
-        Self::Cuboid(Cuboid::from_length(0.1))
-    }
-}
-
 #[derive(Component, Reflect, From)]
 #[reflect(Component)]
 pub enum Mesh3dFlag {
@@ -84,35 +64,19 @@ pub enum MeshWrapper {
 #[repr(transparent)]
 pub struct Mesh3dRepr(Mesh3d);
 
-// impl AssetSynonymTarget for Mesh3dRepr {
-//     type Synonym = Mesh3dFlag;
+impl AssetSynonymTarget for Mesh3dRepr {
+    type Synonym = Mesh3dFlag;
 
-//     type AssetType = Mesh;
+    type AssetType = Mesh;
 
-//     fn from_synonym(value: &SynonymPure<Self>) -> Self::AssetType {
-//         match value {
-//             MeshWrapper::Prefab(mesh_prefab) => mesh_prefab.into(),
-//             MeshWrapper::Procedural(mesh) => mesh,
-//         }
-//     }
-
-//     fn from_asset(value: &Self::AssetType) -> SynonymPure<Self> {
-//         todo!()
-//     }
-// }
-
-// impl AssetSynonym for Mesh3dFlag {
-//     type SynonymTarget = Mesh3d;
-
-//     type PureVariant = MeshWrapper;
+    fn from_synonym(value: &SynonymPure<Self>, _asset_server: &AssetServer) -> Self::AssetType {
+        value.into()
+    }
 
-//     fn asset_state(&self) -> AssetState<Self::PureVariant, String> {
-//         match self {
-//             Self::Pure(material_wrapper) => AssetState::Pure(material_wrapper),
-//             Self::Path(path) => AssetState::Path(path),
-//         }
-//     }
-// }
+    fn from_asset(value: &Self::AssetType) -> SynonymPure<Self> {
+        value.into()
+    }
+}
 
 /// TODO: Implement this a bevy <-> mesh converter for this library exists.
 ///
@@ -0,0 +1,384 @@
+use derive_more::derive::From;
+
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::{
+    mesh::{
+        CapsuleUvProfile, ConeAnchor, CylinderAnchor, CylinderMeshBuilder, MeshBuilder, PrimitiveTopology,
+        SphereKind,
+    },
+    prelude::*,
+    render_asset::RenderAssetUsages,
+};
+
+use bevy_render::mesh::ExtrusionBuilder;
+
+use crate::synonyms::mesh2d::Mesh2dPrefab;
+
+/// bevy prefab meshes + the mesh builder settings they were authored with.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, From)]
+pub enum MeshPrefab {
+    Cuboid(Cuboid),
+    Cylinder(Cylinder, CylinderMeshSettings),
+    Capsule(Capsule3d, Capsule3dMeshSettings),
+    Sphere(Sphere, SphereKindWrapper),
+    Cone(Cone, ConeMeshSettings),
+    Torus(Torus, TorusMeshSettings),
+    Plane(Plane3d, PlaneMeshSettings),
+    ConicalFrustum(ConicalFrustum, ConicalFrustumMeshSettings),
+    Tetrahedron(Tetrahedron),
+    Triangle(Triangle3d),
+    Segment(Segment3d),
+    Extrusion(ExtrusionPrefab, ExtrusionMeshSettings),
+}
+
+impl From<&MeshPrefab> for Mesh {
+    fn from(value: &MeshPrefab) -> Self {
+        match *value {
+            MeshPrefab::Cuboid(cuboid) => cuboid.into(),
+            MeshPrefab::Cylinder(cylinder, settings) => CylinderMeshBuilder {
+                cylinder,
+                resolution: settings.resolution,
+                segments: settings.segments,
+                caps: settings.caps,
+                anchor: settings.anchor.into(),
+            }
+            .build(),
+            MeshPrefab::Capsule(capsule3d, settings) => capsule3d
+                .mesh()
+                .rings(settings.rings)
+                .longitudes(settings.longitudes)
+                .latitudes(settings.latitudes)
+                .uv_profile(settings.uv_profile.into())
+                .build(),
+            MeshPrefab::Sphere(sphere, kind) => sphere.mesh().kind(kind.into()).build(),
+            MeshPrefab::Cone(cone, settings) => cone
+                .mesh()
+                .resolution(settings.resolution)
+                .anchor(settings.anchor.into())
+                .build(),
+            MeshPrefab::Torus(torus, settings) => torus
+                .mesh()
+                .minor_resolution(settings.minor_resolution)
+                .major_resolution(settings.major_resolution)
+                .angle_range(settings.angle_start..=settings.angle_end)
+                .build(),
+            MeshPrefab::Plane(plane3d, settings) => plane3d
+                .mesh()
+                .subdivisions(settings.subdivisions)
+                .build(),
+            MeshPrefab::ConicalFrustum(conical_frustum, settings) => conical_frustum
+                .mesh()
+                .resolution(settings.resolution)
+                .segments(settings.segments)
+                .build(),
+            MeshPrefab::Tetrahedron(tetrahedron) => tetrahedron.into(),
+            MeshPrefab::Triangle(triangle3d) => triangle3d.into(),
+            // bevy has no mesh builder for segments. Build it as a line, with normals and uvs so it renders with
+            // the same materials as other meshes.
+            MeshPrefab::Segment(segment3d) => {
+                let normal = segment3d
+                    .try_direction()
+                    .map_or(Vec3::Y, |n| n.any_orthonormal_vector())
+                    .to_array();
+                Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+                    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, segment3d.vertices.to_vec())
+                    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![normal; 2])
+                    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0], [1.0, 0.0]])
+            }
+            MeshPrefab::Extrusion(extrusion, settings) => extrusion.mesh(settings),
+        }
+    }
+}
+
+impl From<Cylinder> for MeshPrefab {
+    fn from(value: Cylinder) -> Self {
+        Self::Cylinder(value, Default::default())
+    }
+}
+
+impl From<Capsule3d> for MeshPrefab {
+    fn from(value: Capsule3d) -> Self {
+        Self::Capsule(value, Default::default())
+    }
+}
+
+impl From<Sphere> for MeshPrefab {
+    fn from(value: Sphere) -> Self {
+        Self::Sphere(value, Default::default())
+    }
+}
+
+impl From<Cone> for MeshPrefab {
+    fn from(value: Cone) -> Self {
+        Self::Cone(value, Default::default())
+    }
+}
+
+impl From<Torus> for MeshPrefab {
+    fn from(value: Torus) -> Self {
+        Self::Torus(value, Default::default())
+    }
+}
+
+impl From<Plane3d> for MeshPrefab {
+    fn from(value: Plane3d) -> Self {
+        Self::Plane(value, Default::default())
+    }
+}
+
+impl From<ConicalFrustum> for MeshPrefab {
+    fn from(value: ConicalFrustum) -> Self {
+        Self::ConicalFrustum(value, Default::default())
+    }
+}
+
+impl From<ExtrusionPrefab> for MeshPrefab {
+    fn from(value: ExtrusionPrefab) -> Self {
+        Self::Extrusion(value, Default::default())
+    }
+}
+
+impl Default for MeshPrefab {
+    fn default() -> Self {
+        Self::Cuboid(Cuboid::from_length(0.1))
+    }
+}
+
+/// serializable wrapper for [`CylinderAnchor`]
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CylinderAnchorWrapper {
+    #[default]
+    MidPoint,
+    Top,
+    Bottom,
+}
+
+impl From<CylinderAnchorWrapper> for CylinderAnchor {
+    fn from(value: CylinderAnchorWrapper) -> Self {
+        match value {
+            CylinderAnchorWrapper::MidPoint => Self::MidPoint,
+            CylinderAnchorWrapper::Top => Self::Top,
+            CylinderAnchorWrapper::Bottom => Self::Bottom,
+        }
+    }
+}
+
+/// settings for [`Cylinder`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct CylinderMeshSettings {
+    pub resolution: u32,
+    pub segments: u32,
+    pub caps: bool,
+    pub anchor: CylinderAnchorWrapper,
+}
+
+impl Default for CylinderMeshSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 32,
+            segments: 1,
+            caps: true,
+            anchor: CylinderAnchorWrapper::default(),
+        }
+    }
+}
+
+/// serializable wrapper for [`CapsuleUvProfile`]
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CapsuleUvProfileWrapper {
+    #[default]
+    Aspect,
+    Uniform,
+    Fixed,
+}
+
+impl From<CapsuleUvProfileWrapper> for CapsuleUvProfile {
+    fn from(value: CapsuleUvProfileWrapper) -> Self {
+        match value {
+            CapsuleUvProfileWrapper::Aspect => Self::Aspect,
+            CapsuleUvProfileWrapper::Uniform => Self::Uniform,
+            CapsuleUvProfileWrapper::Fixed => Self::Fixed,
+        }
+    }
+}
+
+/// settings for [`Capsule3d`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct Capsule3dMeshSettings {
+    pub rings: u32,
+    pub longitudes: u32,
+    pub latitudes: u32,
+    pub uv_profile: CapsuleUvProfileWrapper,
+}
+
+impl Default for Capsule3dMeshSettings {
+    fn default() -> Self {
+        Self {
+            rings: 0,
+            longitudes: 32,
+            latitudes: 16,
+            uv_profile: CapsuleUvProfileWrapper::default(),
+        }
+    }
+}
+
+/// serializable wrapper for [`SphereKind`]
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SphereKindWrapper {
+    Ico { subdivisions: u32 },
+    Uv { sectors: u32, stacks: u32 },
+}
+
+impl Default for SphereKindWrapper {
+    fn default() -> Self {
+        Self::Ico { subdivisions: 5 }
+    }
+}
+
+impl From<SphereKindWrapper> for SphereKind {
+    fn from(value: SphereKindWrapper) -> Self {
+        match value {
+            SphereKindWrapper::Ico { subdivisions } => Self::Ico { subdivisions },
+            SphereKindWrapper::Uv { sectors, stacks } => Self::Uv { sectors, stacks },
+        }
+    }
+}
+
+/// serializable wrapper for [`ConeAnchor`]
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ConeAnchorWrapper {
+    #[default]
+    MidPoint,
+    Tip,
+    Base,
+}
+
+impl From<ConeAnchorWrapper> for ConeAnchor {
+    fn from(value: ConeAnchorWrapper) -> Self {
+        match value {
+            ConeAnchorWrapper::MidPoint => Self::MidPoint,
+            ConeAnchorWrapper::Tip => Self::Tip,
+            ConeAnchorWrapper::Base => Self::Base,
+        }
+    }
+}
+
+/// settings for [`Cone`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct ConeMeshSettings {
+    pub resolution: u32,
+    pub anchor: ConeAnchorWrapper,
+}
+
+impl Default for ConeMeshSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 32,
+            anchor: ConeAnchorWrapper::default(),
+        }
+    }
+}
+
+/// settings for [`Torus`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct TorusMeshSettings {
+    pub minor_resolution: usize,
+    pub major_resolution: usize,
+    /// start of the swept angle range, in radians.
+    pub angle_start: f32,
+    /// end of the swept angle range, in radians.
+    pub angle_end: f32,
+}
+
+impl Default for TorusMeshSettings {
+    fn default() -> Self {
+        Self {
+            minor_resolution: 24,
+            major_resolution: 32,
+            angle_start: 0.0,
+            angle_end: 2.0 * core::f32::consts::PI,
+        }
+    }
+}
+
+/// settings for [`Plane3d`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Default)]
+pub struct PlaneMeshSettings {
+    pub subdivisions: u32,
+}
+
+/// settings for [`ConicalFrustum`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct ConicalFrustumMeshSettings {
+    pub resolution: u32,
+    pub segments: u32,
+}
+
+impl Default for ConicalFrustumMeshSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 32,
+            segments: 1,
+        }
+    }
+}
+
+/// A 2d prefab extruded along the z axis.
+///
+/// serializable version of [`Extrusion`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct ExtrusionPrefab {
+    pub base_shape: Mesh2dPrefab,
+    pub half_depth: f32,
+}
+
+impl ExtrusionPrefab {
+    pub fn new(base_shape: impl Into<Mesh2dPrefab>, depth: f32) -> Self {
+        Self {
+            base_shape: base_shape.into(),
+            half_depth: depth / 2.0,
+        }
+    }
+
+    fn mesh(&self, settings: ExtrusionMeshSettings) -> Mesh {
+        let depth = self.half_depth * 2.0;
+        let segments = settings.segments;
+        match self.base_shape {
+            Mesh2dPrefab::Circle(circle, shape) => ExtrusionBuilder::new(&circle, depth)
+                .resolution(shape.resolution)
+                .segments(segments)
+                .build(),
+            Mesh2dPrefab::Rectangle(rectangle) => ExtrusionBuilder::new(&rectangle, depth)
+                .segments(segments)
+                .build(),
+            Mesh2dPrefab::RegularPolygon(regular_polygon) => {
+                ExtrusionBuilder::new(&regular_polygon, depth)
+                    .segments(segments)
+                    .build()
+            }
+            Mesh2dPrefab::Annulus(annulus, shape) => ExtrusionBuilder::new(&annulus, depth)
+                .resolution(shape.resolution)
+                .segments(segments)
+                .build(),
+            Mesh2dPrefab::Capsule(capsule2d, shape) => ExtrusionBuilder::new(&capsule2d, depth)
+                .resolution(shape.resolution)
+                .segments(segments)
+                .build(),
+        }
+    }
+}
+
+/// settings for [`ExtrusionPrefab`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct ExtrusionMeshSettings {
+    /// number of segments along the depth of the extrusion. The resolution of round base shapes comes from their
+    /// [`Mesh2dPrefab`] settings.
+    pub segments: usize,
+}
+
+impl Default for ExtrusionMeshSettings {
+    fn default() -> Self {
+        Self { segments: 1 }
+    }
+}
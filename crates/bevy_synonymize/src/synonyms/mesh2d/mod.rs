@@ -0,0 +1,158 @@
+use bevy_derive::Deref;
+use bytemuck::TransparentWrapper;
+use derive_more::derive::From;
+use crate::traits::*;
+
+use bevy_asset::AssetServer;
+use bevy_ecs::prelude::*;
+use bevy_math::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_render::{mesh::MeshBuilder, prelude::*};
+
+/// bevy 2d prefab meshes + the mesh builder settings they were authored with.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, From)]
+pub enum Mesh2dPrefab {
+    Circle(Circle, CircleMeshSettings),
+    Rectangle(Rectangle),
+    RegularPolygon(RegularPolygon),
+    Annulus(Annulus, AnnulusMeshSettings),
+    Capsule(Capsule2d, Capsule2dMeshSettings),
+}
+
+impl From<&Mesh2dPrefab> for Mesh {
+    fn from(value: &Mesh2dPrefab) -> Self {
+        match *value {
+            Mesh2dPrefab::Circle(circle, settings) => circle.mesh().resolution(settings.resolution).build(),
+            Mesh2dPrefab::Rectangle(rectangle) => rectangle.into(),
+            Mesh2dPrefab::RegularPolygon(regular_polygon) => regular_polygon.into(),
+            Mesh2dPrefab::Annulus(annulus, settings) => annulus.mesh().resolution(settings.resolution).build(),
+            Mesh2dPrefab::Capsule(capsule2d, settings) => capsule2d.mesh().resolution(settings.resolution).build(),
+        }
+    }
+}
+
+impl From<Circle> for Mesh2dPrefab {
+    fn from(value: Circle) -> Self {
+        Self::Circle(value, Default::default())
+    }
+}
+
+impl From<Annulus> for Mesh2dPrefab {
+    fn from(value: Annulus) -> Self {
+        Self::Annulus(value, Default::default())
+    }
+}
+
+impl From<Capsule2d> for Mesh2dPrefab {
+    fn from(value: Capsule2d) -> Self {
+        Self::Capsule(value, Default::default())
+    }
+}
+
+impl Default for Mesh2dPrefab {
+    fn default() -> Self {
+        Self::Rectangle(FALLBACK_MESH_2D)
+    }
+}
+
+/// serializable wrapper for 2d meshes.
+#[derive(Component, Reflect, From)]
+#[reflect(Component)]
+pub enum Mesh2dFlag {
+    /// asset path to a model from bevy.
+    Path(String),
+    Pure(Mesh2dWrapper),
+}
+
+impl SynonymPaths for Mesh2dFlag {
+    type Pure = Mesh2dWrapper;
+
+    type Path = String;
+
+    fn asset_state(&self) -> AssetState<SelfPure<Self>, SelfPath<Self>> {
+        match self {
+            Self::Pure(mesh_wrapper) => AssetState::Pure(mesh_wrapper),
+            Self::Path(path) => AssetState::Path(path),
+        }
+    }
+}
+
+impl Default for Mesh2dFlag {
+    fn default() -> Self {
+        Self::Pure(Mesh2dWrapper::Prefab(Mesh2dPrefab::default()))
+    }
+}
+
+#[derive(Reflect, From)]
+pub enum Mesh2dWrapper {
+    Prefab(Mesh2dPrefab),
+    Procedural(Mesh),
+}
+
+impl From<&Mesh2dWrapper> for Mesh {
+    fn from(value: &Mesh2dWrapper) -> Self {
+        match value {
+            Mesh2dWrapper::Prefab(mesh_prefab) => mesh_prefab.into(),
+            Mesh2dWrapper::Procedural(mesh) => mesh.clone(),
+        }
+    }
+}
+
+#[derive(TransparentWrapper, Deref)]
+#[repr(transparent)]
+pub struct Mesh2dRepr(Mesh2d);
+
+impl AssetSynonymTarget for Mesh2dRepr {
+    type Synonym = Mesh2dFlag;
+
+    type AssetType = Mesh;
+
+    fn from_synonym(value: &SynonymPure<Self>, _asset_server: &AssetServer) -> Self::AssetType {
+        value.into()
+    }
+
+    fn from_asset(value: &Self::AssetType) -> SynonymPure<Self> {
+        Mesh2dWrapper::Procedural(value.clone())
+    }
+}
+
+pub const FALLBACK_MESH_2D: Rectangle = Rectangle {
+    half_size: Vec2 { x: 0.1, y: 0.1 },
+};
+
+/// settings for [`Circle`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct CircleMeshSettings {
+    pub resolution: u32,
+}
+
+impl Default for CircleMeshSettings {
+    fn default() -> Self {
+        Self { resolution: 32 }
+    }
+}
+
+/// settings for [`Annulus`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct AnnulusMeshSettings {
+    pub resolution: u32,
+}
+
+impl Default for AnnulusMeshSettings {
+    fn default() -> Self {
+        Self { resolution: 32 }
+    }
+}
+
+/// settings for [`Capsule2d`]'s mesh builder.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct Capsule2dMeshSettings {
+    /// vertices of each of the capsule's semicircles.
+    pub resolution: u32,
+}
+
+impl Default for Capsule2dMeshSettings {
+    fn default() -> Self {
+        Self { resolution: 16 }
+    }
+}
@@ -1,2 +1,8 @@
+#[cfg(feature = "render_2d")]
+pub mod color_material;
+#[cfg(feature = "material")]
 pub mod material;
-pub mod mesh;
\ No newline at end of file
+#[cfg(feature = "mesh")]
+pub mod mesh;
+#[cfg(feature = "mesh")]
+pub mod mesh2d;
@@ -1,4 +1,5 @@
 use crate::{
+    resources::PersistentIds,
     traits::*,
 };
 use std::{
@@ -97,7 +98,7 @@ pub fn desynonymize_assset<Impl>(
                 }
                 AssetState::Pure(wrapper) => {
                     // let new_asset = Impl::AssetType::from(wrapper);
-                    let new_asset = Impl::from_synonym(wrapper);
+                    let new_asset = Impl::from_synonym(wrapper, &asset_server);
 
                     let handle = assets.add(new_asset);
                     SynonymTarget::<Impl>::from(handle)
@@ -123,3 +124,26 @@ pub fn desynonymize<Synonym>(
 }
 
 
+
+/// resolves [`PersistentRef`]s of changed `T`s, and of every `T` when entities gain/lose [`PersistentId`]s.
+///
+/// [`PersistentRef`]: crate::persistent::PersistentRef
+/// [`PersistentId`]: crate::persistent::PersistentId
+pub fn resolve_persistent_refs<T: PersistentRefs>(mut synonyms: Query<&mut T>, ids: Res<PersistentIds>) {
+    let all = ids.is_changed();
+    for mut synonym in &mut synonyms {
+        if !all && !synonym.is_changed() {
+            continue;
+        }
+        let mut retargeted = false;
+        for persistent_ref in synonym.bypass_change_detection().persistent_refs() {
+            let entity = persistent_ref.entity;
+            persistent_ref.resolve(&ids);
+            retargeted |= persistent_ref.entity != entity;
+        }
+        // only retargeting has to reach the synonym target, filling in ids doesn't.
+        if retargeted {
+            synonym.set_changed();
+        }
+    }
+}
@@ -1,8 +1,17 @@
 use bevy_asset::prelude::*;
-use bevy_ecs::prelude::*;
-use bevy_reflect::{FromReflect, GetTypeRegistration, Reflect, Typed};
+use bevy_ecs::{component::Mutable, prelude::*};
+use bevy_reflect::{FromReflect, FromType, GetTypeRegistration, Reflect, Typed};
 use bytemuck::TransparentWrapper;
 use std::ops::Deref;
+
+use crate::persistent::PersistentRef;
+
+/// a serializable component that stands in for [`Self::SynonymTarget`] in saves.
+///
+/// Synonyms that hold [`Entity`]s should mark those fields with `#[entities]`(or implement [`Component::map_entities`] for manual impls),
+/// so they are remapped when loaded from a save/spawned from a prefab. Nested types with entities can `#[derive(MapEntities)]`.
+///
+/// [`MapEntities`]: bevy_ecs::entity::MapEntities
 pub trait ComponentSynonym
 where
     Self: Component
@@ -58,6 +67,34 @@ pub trait SynonymPaths {
     fn asset_state(&self) -> AssetState<SelfPure<Self>, String>;
 }
 
+/// type data for reading/replacing the asset path of an asset synonym through reflection.
+/// Registered for asset synonyms by [`SynonymizeAsset`].
+///
+/// [`SynonymizeAsset`]: crate::plugins::SynonymizeAsset
+#[derive(Clone)]
+pub struct ReflectAssetPath {
+    /// asset path of the synonym, if it refers to its asset by path.
+    pub get: fn(&dyn Reflect) -> Option<String>,
+    /// points the synonym at the asset at the given path.
+    pub set: fn(&mut dyn Reflect, String),
+}
+
+impl<T: SynonymPaths + From<String> + Reflect> FromType<T> for ReflectAssetPath {
+    fn from_type() -> Self {
+        Self {
+            get: |value| match value.downcast_ref::<T>()?.asset_state() {
+                AssetState::Path(path) => Some(path.clone()),
+                AssetState::Pure(_) => None,
+            },
+            set: |value, path| {
+                if let Some(value) = value.downcast_mut::<T>() {
+                    *value = T::from(path);
+                }
+            },
+        }
+    }
+}
+
 pub type SynonymPath<T> = <<T as AssetSynonymTarget>::Synonym as SynonymPaths>::Path; 
 pub type SynonymPure<T> = <<T as AssetSynonymTarget>::Synonym as SynonymPaths>::Pure;
 
@@ -69,10 +106,11 @@ pub trait AssetSynonymTarget
         Self: Deref<Target: From<Handle<Self::AssetType>> + Sized + Component + Deref<Target = Handle<Self::AssetType>>> + TransparentWrapper<Self::Target>,
         Self::Target: Deref,
 {
-    type Synonym: Reflect + FromReflect + GetTypeRegistration + From<String> + From<SynonymPure<Self>> + Component + SynonymPaths;
+    type Synonym: Reflect + FromReflect + Typed + GetTypeRegistration + From<String> + From<SynonymPure<Self>> + Component + SynonymPaths;
     type AssetType: Asset;
 
-    fn from_synonym(value: &SynonymPure<Self>) -> Self::AssetType;
+    /// builds the asset. `asset_server` loads assets the synonym refers to by path(e.g: textures).
+    fn from_synonym(value: &SynonymPure<Self>, asset_server: &AssetServer) -> Self::AssetType;
     
     fn from_asset(value: &Self::AssetType) -> SynonymPure<Self>;
 }
@@ -81,4 +119,11 @@ pub trait AssetSynonymTarget
 //FIXME: make this work with a set of components, or better, change to use a "component iter" to have this work for all components in query
 pub trait ChangeChecked {
     type ChangeCheckedComp: Component;
-}
\ No newline at end of file
+}
+/// synonyms that refer to other entities through [`PersistentRef`]s, so the references hold across sessions and to
+/// entities outside of the save. Refs are resolved by [`ResolvePersistentRefs`].
+///
+/// [`ResolvePersistentRefs`]: crate::plugins::ResolvePersistentRefs
+pub trait PersistentRefs: Component<Mutability = Mutable> {
+    fn persistent_refs(&mut self) -> Vec<&mut PersistentRef>;
+}
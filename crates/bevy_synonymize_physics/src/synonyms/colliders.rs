@@ -1,6 +1,10 @@
 use bevy_log::warn;
-use bevy_math::primitives::{Capsule3d, Cone, Cuboid, Cylinder, Sphere};
-use bevy_rapier3d::prelude::{Collider, ColliderView};
+use bevy_math::{
+    Quat, Vec3,
+    primitives::{Capsule3d, Cone, Cuboid, Cylinder, Plane3d, Sphere},
+};
+use bevy_rapier3d::prelude::{Collider, ColliderView, ComputedColliderShape, TriMeshFlags, VHACDParameters};
+use bevy_render::prelude::*;
 use bevy_synonymize::{
     prelude::mesh::{FALLBACK_MESH, MeshPrefab},
     traits::ComponentSynonym,
@@ -37,6 +41,9 @@ pub struct IgnoredCollider(#[reflect(ignore)] Option<Collider>, String);
 #[reflect(Component)]
 #[require(CcdFlag, CollisionGroupsFlag, SolverGroupsFlag)]
 pub enum ColliderFlag {
+    /// collider shaped like the prefab's mesh. Prefabs without a rapier primitive are approximated: planes are thin
+    /// cuboids, extrusions are decomposed into convex parts, and tori are trimeshes(hollow, and slow to collide
+    /// against. Prefer a compound of simpler colliders for dynamic bodies).
     Prefab(MeshPrefab),
     /// ignored variant of collider for unimplemented collider kinds.
     Ignore(IgnoredCollider),
@@ -59,28 +66,49 @@ impl From<&ColliderFlag> for Collider {
                     MeshPrefab::Cuboid(cuboid) => {
                         Collider::cuboid(cuboid.half_size.x, cuboid.half_size.y, cuboid.half_size.z)
                     }
-                    MeshPrefab::Cylinder(cylinder) => {
+                    MeshPrefab::Cylinder(cylinder, _) => {
                         Collider::cylinder(cylinder.half_height, cylinder.radius)
                     }
-                    MeshPrefab::Capsule(capsule3d) => {
+                    MeshPrefab::Capsule(capsule3d, _) => {
                         //TODO: double check that is is correct
                         Collider::capsule_y(capsule3d.half_length, capsule3d.radius)
                     }
-                    MeshPrefab::Sphere(sphere) => Collider::ball(sphere.radius),
-                    MeshPrefab::Unimplemented(unimplemented) => {
+                    MeshPrefab::Sphere(sphere, _) => Collider::ball(sphere.radius),
+                    MeshPrefab::Cone(cone, _) => Collider::cone(cone.height * 0.5, cone.radius),
+                    MeshPrefab::Tetrahedron(tetrahedron) => {
+                        Collider::convex_hull(&tetrahedron.vertices).unwrap_or_else(|| {
+                            warn!("Degenerate tetrahedron {:#?}. Using fallback instead.", tetrahedron);
+                            fallback_collider()
+                        })
+                    }
+                    MeshPrefab::Triangle(triangle3d) => {
+                        let [a, b, c] = triangle3d.vertices;
+                        Collider::triangle(a, b, c)
+                    }
+                    MeshPrefab::Segment(segment3d) => {
+                        let [a, b] = segment3d.vertices;
+                        Collider::segment(a, b)
+                    }
+                    MeshPrefab::ConicalFrustum(..) => {
+                        mesh_collider(mesh_prefab, &ComputedColliderShape::ConvexHull)
+                    }
+                    MeshPrefab::Plane(plane3d, _) => plane_collider(plane3d),
+                    // extrusions of round/concave shapes(e.g: annuli) aren't convex.
+                    MeshPrefab::Extrusion(..) => mesh_collider(
+                        mesh_prefab,
+                        &ComputedColliderShape::ConvexDecomposition(VHACDParameters::default()),
+                    ),
+                    // no rapier primitive, and not something convex decomposition approximates well.
+                    MeshPrefab::Torus(..) => {
                         warn!(
-                            "Attempted to convert unimplemented shape: {:#} to collider. Using fallback instead.",
-                            unimplemented
+                            "{:#?} has no collider primitive. Using a trimesh collider, which is hollow and slow against dynamic bodies",
+                            mesh_prefab
                         );
-
-                        // Fallback mesh is a cuboid as the (more accurate) alternative would be performance dropping to 0.1fps from a dozen thosand face trimesh collider.
-                        Collider::cuboid(
-                            FALLBACK_MESH.half_size.x,
-                            FALLBACK_MESH.half_size.z,
-                            FALLBACK_MESH.half_size.z,
+                        mesh_collider(
+                            mesh_prefab,
+                            &ComputedColliderShape::TriMesh(TriMeshFlags::default()),
                         )
                     }
-                    MeshPrefab::Cone(cone) => Collider::cone(cone.height * 0.5, cone.radius),
                 }
             }
             ColliderFlag::Ignore(ignored_collider) => ignored_collider.0.clone().unwrap(),
@@ -88,6 +116,42 @@ impl From<&ColliderFlag> for Collider {
     }
 }
 
+/// Fallback collider for prefabs that could not be converted.
+///
+/// Fallback mesh is a cuboid as the (more accurate) alternative would be performance dropping to 0.1fps from a dozen thosand face trimesh collider.
+fn fallback_collider() -> Collider {
+    Collider::cuboid(
+        FALLBACK_MESH.half_size.x,
+        FALLBACK_MESH.half_size.y,
+        FALLBACK_MESH.half_size.z,
+    )
+}
+
+/// half of the thickness of plane colliders.
+const PLANE_HALF_THICKNESS: f32 = 0.01;
+
+/// thin cuboid under `plane`, with its top face on the plane. Trimesh planes are one sided and easy to tunnel through.
+fn plane_collider(plane: &Plane3d) -> Collider {
+    let normal = *plane.normal;
+    let cuboid = Collider::cuboid(plane.half_size.x, PLANE_HALF_THICKNESS, plane.half_size.y);
+    Collider::compound(vec![(
+        -normal * PLANE_HALF_THICKNESS,
+        Quat::from_rotation_arc(Vec3::Y, normal),
+        cuboid,
+    )])
+}
+
+/// builds a collider from the mesh of a prefab without an equivalent rapier primitive.
+fn mesh_collider(prefab: &MeshPrefab, shape: &ComputedColliderShape) -> Collider {
+    Collider::from_bevy_mesh(&Mesh::from(prefab), shape).unwrap_or_else(|| {
+        warn!(
+            "Could not build collider for {:#?}. Using fallback instead.",
+            prefab
+        );
+        fallback_collider()
+    })
+}
+
 impl From<&Collider> for ColliderFlag {
     fn from(value: &Collider) -> Self {
         let collider = value.as_unscaled_typed_shape();
@@ -151,6 +215,9 @@ impl From<&Collider> for ColliderFlag {
             ColliderView::RoundConvexPolyhedron(view) => {
                 Self::Ignore(((value.clone()).into(), format!("{:#?}", view.raw)).into())
             }
+            ColliderView::Voxels(view) => {
+                Self::Ignore(((value.clone()).into(), format!("{:#?}", view.raw)).into())
+            }
         }
     }
 }
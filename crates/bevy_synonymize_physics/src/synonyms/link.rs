@@ -17,6 +17,7 @@ use rapier3d::{
 
 use bevy_ecs::{
     component::{Mutable, StorageType},
+    entity::EntityMapper,
     prelude::*,
     query::QueryData,
 };
@@ -346,42 +347,43 @@ impl Component for JointFlag {
         _hooks.on_add(|mut world, hook| {
             // rapier joint positions affect transform, but do not affect transformation unless they're part of an active rigidbody.
             // to prevent rebound from joint being snapped on by joint, add transform onto this entity to automatically snap it to where its supposed to be
-            let new_trans = {
-                let comp = match world.entity(hook.entity).get::<Self>() {
-                    Some(val) => val,
-                    None => {
-                        warn!(
-                            "could not get {:#?} on: {:#}",
-                            type_name::<Self>(),
-                            hook.entity
-                        );
-                        return;
-                    }
-                };
-                let Some(parent_trans) = world.get::<Transform>(comp.parent) else {
-                    warn!("parent {:#?} has no trans?", comp.parent);
+            //
+            // deferred, as the parent may not have its transform yet when spawned from the same scene/save.
+            let e = hook.entity;
+            world.commands().queue(move |world: &mut World| {
+                // may have been despawned since, e.g: unloaded in the same frame it was loaded.
+                let Ok(entity) = world.get_entity(e) else {
                     return;
                 };
+                let new_trans = {
+                    let Some(comp) = entity.get::<Self>() else {
+                        warn!("could not get {:#?} on: {:#}", type_name::<Self>(), e);
+                        return;
+                    };
+                    let Some(parent_trans) = world.get::<Transform>(comp.parent) else {
+                        warn!("parent {:#?} has no trans?", comp.parent);
+                        return;
+                    };
 
-                let new_translation = parent_trans.translation
-                    + comp.joint.local_frame1.translation
-                    - comp.joint.local_frame2.translation;
-
-                let new_result = Transform::from_translation(new_translation)
-                    .with_rotation(parent_trans.rotation);
-                //println!("new result: {:#?}", new_result);
-                new_result
-                //parent_trans.translation + comp.joint.local_frame1.translation
-            };
-
-            world.commands().entity(hook.entity).insert(new_trans);
+                    let new_translation = parent_trans.translation
+                        + comp.joint.local_frame1.translation
+                        - comp.joint.local_frame2.translation;
 
-            // let Some(parent) =
-            // let Some(parent_trans) = world.entity(comp.parent_id).get::<Tran
-            //world.commands().entity(e).insert()
+                    Transform::from_translation(new_translation)
+                        .with_rotation(parent_trans.rotation)
+                };
+                if let Ok(mut e) = world.get_entity_mut(e) {
+                    e.insert(new_trans);
+                }
+            });
         });
     }
 
+    /// keeps [`Self::parent`] pointing at the right entity when loaded from a save/spawned from a prefab.
+    fn map_entities<E: EntityMapper>(this: &mut Self, mapper: &mut E) {
+        this.parent = mapper.get_mapped(this.parent);
+    }
+
     type Mutability = Mutable;
 }
 
@@ -474,3 +476,62 @@ impl From<&MotorModelWrapper> for MotorModel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::EntityHashMap;
+    use bevy_scene::{DynamicSceneBuilder, ron, serde::SceneDeserializer};
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<JointFlag>();
+            registry.register::<Transform>();
+        }
+        world
+    }
+
+    #[test]
+    fn joint_parent_is_remapped_on_load() {
+        let mut world = world();
+        let parent = world.spawn(Transform::from_xyz(1.0, 2.0, 3.0)).id();
+        let child = world
+            .spawn(JointFlag {
+                parent,
+                joint: JointInfo::default(),
+            })
+            .id();
+        world.flush();
+
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let save = DynamicSceneBuilder::from_world(&world)
+            .extract_entities([parent, child].into_iter())
+            .build()
+            .serialize(&registry.read())
+            .unwrap();
+        let mut deserializer = ron::de::Deserializer::from_str(&save).unwrap();
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .unwrap();
+
+        let mut entity_map = EntityHashMap::default();
+        scene.write_to_world(&mut world, &mut entity_map).unwrap();
+        world.flush();
+        let (loaded_parent, loaded_child) = (entity_map[&parent], entity_map[&child]);
+        assert_ne!(loaded_parent, parent);
+        assert_eq!(world.get::<JointFlag>(loaded_child).unwrap().parent, loaded_parent);
+        // snapped to the loaded parent.
+        assert_eq!(
+            world.get::<Transform>(loaded_child).unwrap().translation,
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+    }
+}
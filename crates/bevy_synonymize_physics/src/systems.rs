@@ -95,7 +95,7 @@ pub fn collider_from_farthest_points(
                     largest = candidate;
                 }
             }
-            Either::Left(ColliderFlag::Prefab(MeshPrefab::Sphere(Sphere::new(
+            Either::Left(ColliderFlag::Prefab(MeshPrefab::from(Sphere::new(
                 largest,
             ))))
         }
@@ -114,7 +114,7 @@ pub fn collider_from_farthest_points(
                     largest = candidate;
                 }
             }
-            Either::Left(ColliderFlag::Prefab(MeshPrefab::Sphere(Sphere::new(
+            Either::Left(ColliderFlag::Prefab(MeshPrefab::from(Sphere::new(
                 largest,
             ))))
         }
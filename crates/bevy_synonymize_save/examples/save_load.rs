@@ -7,7 +7,11 @@ use bevy_inspector_egui::{
     egui::{self, TextEdit},
 };
 use bevy_synonymize::{plugins::SynonymizeBasePlugin, prelude::material::Material3dFlag};
-use bevy_synonymize_save::{plugins::{SerializationPlugin}, resources::{LoadRequest, SaveRequest}};
+use bevy_synonymize_save::{
+    events::{LoadCompleted, LoadFailed, SaveCompleted, SaveFailed},
+    plugins::SerializationPlugin,
+    traits::SaveWorldCommands,
+};
 use bevy_ui_extras::{UiExtrasDebug, states::DebugMenuState};
 use moonshine_save::save::Save;
 use std::{env, path::PathBuf};
@@ -33,6 +37,7 @@ fn main() {
         })
         .add_systems(Startup, setup)
         .add_systems(Update, save_file_selection)
+        .add_systems(Update, log_save_results)
         //TODO: re-add when this has been re-implemented.
         //.add_systems(Update, serialization_widgets_ui)
         .run();
@@ -131,20 +136,16 @@ pub fn save_file_selection(
 
                 ui.horizontal(|ui| {
                     if ui.button("save").clicked() {
-                        commands.insert_resource(SaveRequest {
-                            path: SAVES_LOCATION.to_owned()
-                                + "/"
-                                + &save_file_textbox.name
-                                + ".ron",
-                        })
+                        commands.save_world(
+                            SAVES_LOCATION.to_owned() + "/" + &save_file_textbox.name + ".ron",
+                            default(),
+                        )
                     }
                     if ui.button("load").clicked() {
-                        commands.insert_resource(LoadRequest {
-                            path: SAVES_LOCATION.to_owned()
-                                + "/"
-                                + &save_file_textbox.name
-                                + ".ron",
-                        })
+                        commands.load_world(
+                            SAVES_LOCATION.to_owned() + "/" + &save_file_textbox.name + ".ron",
+                            default(),
+                        )
                     }
                 });
             });
@@ -168,6 +169,29 @@ pub fn save_file_selection(
     }
 }
 
+pub fn log_save_results(
+    mut saved: EventReader<SaveCompleted>,
+    mut save_failed: EventReader<SaveFailed>,
+    mut loaded: EventReader<LoadCompleted>,
+    mut load_failed: EventReader<LoadFailed>,
+) {
+    for event in saved.read() {
+        info!(
+            "saved {:#} entities({:#} bytes) to {:#}",
+            event.entity_count, event.bytes, event.path
+        );
+    }
+    for event in save_failed.read() {
+        warn!("save to {:#} failed: {:#}", event.path, event.error);
+    }
+    for event in loaded.read() {
+        info!("loaded {:#} entities from {:#}", event.entity_count, event.path);
+    }
+    for event in load_failed.read() {
+        warn!("load from {:#} failed: {:#}", event.path, event.error);
+    }
+}
+
 #[derive(Default, EnumIter, Display)]
 pub enum UtilityType {
     #[default]
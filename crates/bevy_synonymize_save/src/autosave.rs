@@ -0,0 +1,85 @@
+//! autosaving into a rotating set of slots. See [`AutosavePlugin`].
+//!
+//! [`AutosavePlugin`]: crate::plugins::AutosavePlugin
+
+use std::{cmp::Reverse, fs, time::Duration};
+
+use bevy_ecs::{component::Tick, prelude::*};
+
+use crate::resources::SaveOptions;
+
+/// settings for [`AutosavePlugin`]. Can be changed at runtime.
+///
+/// [`AutosavePlugin`]: crate::plugins::AutosavePlugin
+#[derive(Resource, Clone, Debug)]
+pub struct AutosaveSettings {
+    /// time between autosaves. `None` to only autosave on [`TriggerAutosave`].
+    ///
+    /// [`TriggerAutosave`]: crate::events::TriggerAutosave
+    pub interval: Option<Duration>,
+    /// folder autosave slots are written to.
+    pub folder: String,
+    /// number of slots(`autosave_0..slots`) autosaves rotate through.
+    pub slots: usize,
+    pub options: SaveOptions,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            interval: Some(Duration::from_secs(300)),
+            folder: "saves".to_owned(),
+            slots: 3,
+            options: SaveOptions::default(),
+        }
+    }
+}
+
+impl AutosaveSettings {
+    /// path of the given autosave slot.
+    pub fn slot_path(&self, slot: usize) -> String {
+        format!(
+            "{}/autosave_{}.{}",
+            self.folder.trim_end_matches('/'),
+            slot,
+            self.options.format.extension()
+        )
+    }
+
+    /// paths of the autosave slots that exist, newest first. Pass to [`SaveWorldCommands::load_first_intact`] to load
+    /// the newest autosave that isn't corrupted.
+    ///
+    /// [`SaveWorldCommands::load_first_intact`]: crate::traits::SaveWorldCommands::load_first_intact
+    pub fn slot_paths_newest_first(&self) -> Vec<String> {
+        let mut slots = (0..self.slots)
+            .map(|n| self.slot_path(n))
+            .filter_map(|n| {
+                let modified = fs::metadata(&n).ok()?.modified().ok()?;
+                Some((n, modified))
+            })
+            .collect::<Vec<_>>();
+        slots.sort_by_key(|n| Reverse(n.1));
+        slots.into_iter().map(|(n, _)| n).collect()
+    }
+
+    /// slot that was written to most recently, going by file modification time.
+    pub fn newest_slot(&self) -> Option<usize> {
+        (0..self.slots)
+            .filter_map(|n| Some((n, fs::metadata(self.slot_path(n)).ok()?.modified().ok()?)))
+            .max_by_key(|(_, modified)| *modified)
+            .map(|(n, _)| n)
+    }
+}
+
+/// progress towards the next autosave.
+#[derive(Resource, Default, Debug)]
+pub struct AutosaveState {
+    /// slot the next autosave is written to.
+    pub next_slot: usize,
+    /// time since the last autosave.
+    pub elapsed: Duration,
+    /// whether an autosave should be attempted this frame.
+    pub due: bool,
+    /// change tick of the last autosave, and how many entities were saved then.
+    pub last_autosave: Option<(Tick, usize)>,
+}
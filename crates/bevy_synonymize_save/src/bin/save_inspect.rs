@@ -0,0 +1,174 @@
+//! inspects save files without running the app, against the types registered by the workspace's plugins.
+//!
+//! ```text
+//! save_inspect list <save> [--with <component>]
+//! save_inspect show <save> <entity> <component>
+//! save_inspect diff <save> <other save>
+//! ```
+//!
+//! Components are named by full or short type path, entities as they are listed. Entities are diffed by
+//! [`PersistentId`] if they have one, or by their id in the save otherwise.
+
+use std::{collections::BTreeMap, error::Error, process};
+
+use bevy_ecs::{prelude::*, reflect::AppTypeRegistry};
+use bevy_reflect::{FromReflect, TypeRegistry};
+use bevy_scene::{DynamicEntity, DynamicScene};
+use bevy_synonymize::persistent::PersistentId;
+use bevy_synonymize_save::{
+    cli::{is_type, read_scene, short_type_path, to_ron, workspace_app},
+    resources::LoadReport,
+};
+
+const USAGE: &str = "usage:
+    save_inspect list <save> [--with <component>]
+    save_inspect show <save> <entity> <component>
+    save_inspect diff <save> <other save>";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let app = workspace_app();
+    let world = app.world();
+
+    let result = match args.as_slice() {
+        ["list", save] => list(world, save, None),
+        ["list", save, "--with", component] => list(world, save, Some(component)),
+        ["show", save, entity, component] => show(world, save, entity, component),
+        ["diff", save, other] => diff(world, save, other),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("{:#}", err);
+        process::exit(1);
+    }
+}
+
+fn list(world: &World, save: &str, with: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let scene = load(world, save)?;
+    for entity in &scene.entities {
+        if with.is_some_and(|with| !entity.components.iter().any(|n| is_type(n.as_partial_reflect(), with))) {
+            continue;
+        }
+        println!("{}", entity.entity);
+        for component in &entity.components {
+            println!("    {}", short_type_path(component.as_partial_reflect()));
+        }
+    }
+    Ok(())
+}
+
+fn show(world: &World, save: &str, entity: &str, component: &str) -> Result<(), Box<dyn Error>> {
+    let scene = load(world, save)?;
+    let entity = scene
+        .entities
+        .iter()
+        .find(|n| n.entity.to_string() == entity || n.entity.to_bits().to_string() == entity)
+        .ok_or_else(|| format!("no entity {} in {}", entity, save))?;
+    let value = entity
+        .components
+        .iter()
+        .find(|n| is_type(n.as_partial_reflect(), component))
+        .ok_or_else(|| format!("{} has no {}", entity.entity, component))?;
+
+    let registry = world.resource::<AppTypeRegistry>().read();
+    println!("{}", to_ron(value.as_partial_reflect(), &registry, true));
+    Ok(())
+}
+
+fn diff(world: &World, save: &str, other: &str) -> Result<(), Box<dyn Error>> {
+    let before = by_key(load(world, save)?);
+    let after = by_key(load(world, other)?);
+    let registry = world.resource::<AppTypeRegistry>().read();
+
+    for (key, entity) in &before {
+        if !after.contains_key(key) {
+            println!("- {} ({})", key, entity.entity);
+        }
+    }
+    for (key, entity) in &after {
+        let Some(old) = before.get(key) else {
+            println!("+ {} ({})", key, entity.entity);
+            continue;
+        };
+        let changes = diff_entity(old, entity, &registry);
+        if !changes.is_empty() {
+            println!("~ {} ({} -> {})", key, old.entity, entity.entity);
+            for change in changes {
+                println!("    {}", change);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// added, removed and changed components, one line each.
+fn diff_entity(old: &DynamicEntity, new: &DynamicEntity, registry: &TypeRegistry) -> Vec<String> {
+    let mut changes = Vec::new();
+    for component in &old.components {
+        let path = short_type_path(component.as_partial_reflect());
+        if !new.components.iter().any(|n| short_type_path(n.as_partial_reflect()) == path) {
+            changes.push(format!("- {}", path));
+        }
+    }
+    for component in &new.components {
+        let component = component.as_partial_reflect();
+        let path = short_type_path(component);
+        match old
+            .components
+            .iter()
+            .find(|n| short_type_path(n.as_partial_reflect()) == path)
+        {
+            None => changes.push(format!("+ {}: {}", path, to_ron(component, registry, false))),
+            Some(before) if before.reflect_partial_eq(component) != Some(true) => {
+                let before = to_ron(before.as_partial_reflect(), registry, false);
+                let after = to_ron(component, registry, false);
+                // types without `PartialEq` reflection compare by their ron instead.
+                if before != after {
+                    changes.push(format!("~ {}: {} -> {}", path, before, after));
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    changes
+}
+
+/// entities of `scene` by persistent id, or by id in the save if they have none.
+fn by_key(scene: DynamicScene) -> BTreeMap<String, DynamicEntity> {
+    scene
+        .entities
+        .into_iter()
+        .map(|entity| {
+            let key = entity
+                .components
+                .iter()
+                .find_map(|n| PersistentId::from_reflect(n.as_partial_reflect()))
+                .map(|n| n.0.to_string())
+                .unwrap_or_else(|| entity.entity.to_string());
+            (key, entity)
+        })
+        .collect()
+}
+
+fn load(world: &World, save: &str) -> Result<DynamicScene, Box<dyn Error>> {
+    let (scene, report) = read_scene(world, save)?;
+    warn_skipped(save, &report);
+    Ok(scene)
+}
+
+fn warn_skipped(save: &str, report: &LoadReport) {
+    if report.is_complete() {
+        return;
+    }
+    let mut skipped = report
+        .skipped_components
+        .keys()
+        .chain(&report.skipped_resources)
+        .collect::<Vec<_>>();
+    skipped.sort();
+    eprintln!("skipped types the workspace doesn't register in {}: {:?}", save, skipped);
+}
@@ -0,0 +1,25 @@
+//! prints a json schema of every synonym registered by the workspace's plugins. See [`bevy_synonymize_save::schema`].
+//!
+//! `cargo run -p bevy_synonymize_save --features cli --bin synonym_schema > synonyms.schema.json`
+
+use bevy_ecs::reflect::AppTypeRegistry;
+use bevy_synonymize::resources::SynonymTargets;
+use bevy_synonymize_save::{cli::workspace_app, schema::synonym_schema};
+
+fn main() {
+    let app = workspace_app();
+    let world = app.world();
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let synonyms = world
+        .get_resource::<SynonymTargets>()
+        .expect("no synonyms were registered");
+
+    let schema = synonym_schema(&registry, synonyms);
+    match serde_json::to_string_pretty(&schema) {
+        Ok(schema) => println!("{}", schema),
+        Err(err) => {
+            eprintln!("could not write schema: {:#}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! self contained save bundles: a save, plus every asset its synonyms refer to by path.
+//!
+//! A bundle is a folder(`save.<ext>` + `assets/`), or the same files in a single archive file. Asset paths in the save
+//! are rewritten to `bundle://<bundle name>/assets/..`, which [`SaveBundlePlugin`] serves from memory once the bundle is
+//! loaded. Bundles load like any other save.
+//!
+//! Only the file each asset path points at is bundled. Assets that load other files by relative path(e.g: gltf with
+//! external buffers/textures) need those embedded to survive bundling.
+//!
+//! [`SaveBundlePlugin`]: crate::plugins::SaveBundlePlugin
+
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use bevy_asset::{
+    AssetPath,
+    io::{AssetSourceId, memory::Dir},
+};
+use bevy_ecs::prelude::*;
+use bevy_scene::DynamicScene;
+use bevy_synonymize::traits::ReflectAssetPath;
+
+#[cfg(feature = "render")]
+use crate::pipeline::offload_procedural_meshes;
+use crate::{
+    events::{LoadError, SaveCompleted, SaveError},
+    pipeline::{encode_scene, extract_save_scene, write_atomic},
+    resources::{SaveOptions, SavePath},
+    slots::{SaveHeader, SaveMetadata},
+};
+
+/// name of the asset source bundled assets are loaded from.
+pub const BUNDLE_SOURCE: &str = "bundle";
+
+/// magic bytes at the start of bundle archives.
+pub const BUNDLE_MAGIC: [u8; 4] = *b"BSXA";
+
+/// folder bundled assets go in, inside of the bundle.
+const BUNDLE_ASSETS: &str = "assets";
+
+/// file stem of the save inside of a bundle.
+const BUNDLE_SAVE: &str = "save";
+
+/// how a save bundle is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BundleKind {
+    /// a folder with the save and an `assets` folder in it.
+    #[default]
+    Folder,
+    /// a single archive file.
+    Archive,
+}
+
+/// where bundled assets are read from when exporting, and served from once loaded.
+#[derive(Resource, Clone)]
+pub struct SaveBundles {
+    /// folder assets in the default asset source are read from. Same as [`AssetPlugin::file_path`].
+    ///
+    /// [`AssetPlugin::file_path`]: bevy_asset::AssetPlugin::file_path
+    pub asset_folder: String,
+    /// assets of loaded bundles, served from the `bundle://` asset source.
+    pub assets: Dir,
+}
+
+/// exports every [`Save`] entity, along with the assets its synonyms refer to by path, as a bundle at `path`.
+pub fn export_bundle(
+    world: &mut World,
+    path: &str,
+    kind: BundleKind,
+    options: &SaveOptions,
+) -> Result<SaveCompleted, SaveError> {
+    let mut scene = extract_save_scene(world, options)?;
+    #[cfg(feature = "render")]
+    offload_procedural_meshes(world, &mut scene, options);
+    let assets = rewrite_asset_paths(world, &mut scene, &bundle_name(path));
+
+    let metadata = world.get_resource::<SaveMetadata>().cloned().unwrap_or_default();
+    let content = encode_scene(world, &scene, options.format)?;
+    let save = SaveHeader::new(&metadata, scene.entities.len()).seal(&content)?;
+
+    let asset_folder = world
+        .get_resource::<SaveBundles>()
+        .map(|n| n.asset_folder.clone())
+        .unwrap_or_else(|| "assets".to_owned());
+    let mut files = vec![(
+        format!("{}.{}", BUNDLE_SAVE, options.format.extension()),
+        save,
+    )];
+    for asset in assets {
+        let source = Path::new(&asset_folder).join(&asset);
+        match fs::read(&source) {
+            Ok(bytes) => files.push((format!("{}/{}", BUNDLE_ASSETS, asset), bytes)),
+            Err(err) => {
+                log::warn!("could not bundle asset {:#?}: {:#}. Skipping", source, err);
+                continue;
+            }
+        }
+        let mut meta = source.into_os_string();
+        meta.push(".meta");
+        if let Ok(bytes) = fs::read(&meta) {
+            files.push((format!("{}/{}.meta", BUNDLE_ASSETS, asset), bytes));
+        }
+    }
+
+    let bytes = files.iter().map(|(_, n)| n.len()).sum();
+    match kind {
+        BundleKind::Folder => {
+            for (name, data) in &files {
+                let file = Path::new(path).join(name);
+                if let Some(parent) = file.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                write_atomic(file, data)?;
+            }
+        }
+        BundleKind::Archive => write_atomic(path, &write_archive(&files))?,
+    }
+
+    Ok(SaveCompleted {
+        path: SavePath::File(path.to_owned()),
+        entity_count: scene.entities.len(),
+        bytes,
+    })
+}
+
+/// points asset paths of synonyms in `scene` into the bundle `name`. Returns the paths(in the default asset source) to bundle.
+fn rewrite_asset_paths(world: &World, scene: &mut DynamicScene, name: &str) -> BTreeSet<String> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let mut assets = BTreeSet::new();
+    let components = scene.entities.iter_mut().flat_map(|n| n.components.iter_mut());
+    for component in components {
+        let Some(asset_path) = component
+            .get_represented_type_info()
+            .and_then(|n| registry.get(n.type_id()))
+            .and_then(|n| n.data::<ReflectAssetPath>())
+        else {
+            continue;
+        };
+        let Some(component) = component.try_as_reflect_mut() else {
+            continue;
+        };
+        let Some(path) = (asset_path.get)(component) else {
+            continue;
+        };
+
+        let parsed = AssetPath::parse(&path);
+        if parsed.source() != &AssetSourceId::Default {
+            log::warn!("can only bundle assets from the default asset source. Not bundling {:#}", path);
+            continue;
+        }
+        let file = parsed.path().to_string_lossy().replace('\\', "/");
+        let bundled = match parsed.label() {
+            Some(label) => format!("{}://{}/{}/{}#{}", BUNDLE_SOURCE, name, BUNDLE_ASSETS, file, label),
+            None => format!("{}://{}/{}/{}", BUNDLE_SOURCE, name, BUNDLE_ASSETS, file),
+        };
+        (asset_path.set)(component, bundled);
+        assets.insert(file);
+    }
+    assets
+}
+
+/// whether `path` is a bundle rather than a plain save. Only reads the first few bytes of files.
+pub fn is_bundle(path: &str) -> bool {
+    let path = Path::new(path);
+    if path.is_dir() {
+        return true;
+    }
+    let mut magic = [0; 4];
+    fs::File::open(path)
+        .and_then(|mut n| n.read_exact(&mut magic))
+        .is_ok_and(|_| magic == BUNDLE_MAGIC)
+}
+
+/// reads the save out of the bundle at `path`, and makes its assets loadable from `bundle://<bundle name>/`.
+///
+/// Returns the save, and its file extension.
+pub fn read_bundle(world: &World, path: &str) -> Result<(Vec<u8>, Option<String>), LoadError> {
+    let files = match Path::new(path).is_dir() {
+        true => read_folder(Path::new(path))?,
+        false => read_archive(&fs::read(path)?).ok_or(LoadError::CorruptBundle)?,
+    };
+    unpack_bundle(world, path, files)
+}
+
+/// [`read_bundle`] for the archive at `path`, already read into `archive`.
+pub(crate) fn read_bundle_archive(
+    world: &World,
+    path: &str,
+    archive: &[u8],
+) -> Result<(Vec<u8>, Option<String>), LoadError> {
+    let files = read_archive(archive).ok_or(LoadError::CorruptBundle)?;
+    unpack_bundle(world, path, files)
+}
+
+/// hands the assets in `files` to [`SaveBundles`], returning the save and its file extension.
+fn unpack_bundle(
+    world: &World,
+    path: &str,
+    files: Vec<(String, Vec<u8>)>,
+) -> Result<(Vec<u8>, Option<String>), LoadError> {
+    let Some(bundles) = world.get_resource::<SaveBundles>() else {
+        return Err(LoadError::NoBundleSource);
+    };
+    let root = PathBuf::from(bundle_name(path));
+    let mut save = None;
+    for (name, data) in files {
+        if let Some(extension) = name
+            .strip_prefix(BUNDLE_SAVE)
+            .and_then(|n| n.strip_prefix('.'))
+        {
+            save = Some((data, Some(extension.to_owned())));
+        } else if let Some(asset) = name.strip_suffix(".meta") {
+            bundles.assets.insert_meta(&root.join(asset), data);
+        } else {
+            bundles.assets.insert_asset(&root.join(name), data);
+        }
+    }
+    save.ok_or(LoadError::NoBundleSave)
+}
+
+/// name assets of the bundle at `path` are under.
+fn bundle_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| BUNDLE_SAVE.to_owned())
+}
+
+/// files in a bundle folder, by path relative to it.
+fn read_folder(folder: &Path) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut folders = vec![folder.to_owned()];
+    while let Some(dir) = folders.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                folders.push(path);
+            } else if let Ok(relative) = path.strip_prefix(folder) {
+                let name = relative.to_string_lossy().replace('\\', "/");
+                files.push((name, fs::read(&path)?));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// [`BUNDLE_MAGIC`], then for each file: name length(u32), name, data length(u64), data. Little endian.
+fn write_archive(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut archive = BUNDLE_MAGIC.to_vec();
+    for (name, data) in files {
+        archive.extend((name.len() as u32).to_le_bytes());
+        archive.extend(name.as_bytes());
+        archive.extend((data.len() as u64).to_le_bytes());
+        archive.extend(data);
+    }
+    archive
+}
+
+/// files in an archive made by [`write_archive`]. `None` if it is cut short or malformed.
+fn read_archive(archive: &[u8]) -> Option<Vec<(String, Vec<u8>)>> {
+    let mut rest = archive.strip_prefix(&BUNDLE_MAGIC)?;
+    let mut files = Vec::new();
+    while !rest.is_empty() {
+        let name_len = u32::from_le_bytes(take(&mut rest, 4)?.try_into().ok()?);
+        let name = take(&mut rest, name_len as u64)?;
+        let data_len = u64::from_le_bytes(take(&mut rest, 8)?.try_into().ok()?);
+        let data = take(&mut rest, data_len)?;
+        files.push((std::str::from_utf8(name).ok()?.to_owned(), data.to_vec()));
+    }
+    Some(files)
+}
+
+/// splits `len` bytes off the front of `rest`. Lengths are read from the archive, so they're checked against what is
+/// left of it before anything is allocated for them.
+fn take<'a>(rest: &mut &'a [u8], len: u64) -> Option<&'a [u8]> {
+    let len = usize::try_from(len).ok().filter(|n| *n <= rest.len())?;
+    let (taken, tail) = rest.split_at(len);
+    *rest = tail;
+    Some(taken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<(String, Vec<u8>)> {
+        vec![
+            ("save.ron".to_owned(), b"(entities: {})".to_vec()),
+            ("assets/models/crate.glb".to_owned(), vec![0, 1, 2, 3, 255]),
+            ("assets/empty.png".to_owned(), Vec::new()),
+        ]
+    }
+
+    #[test]
+    fn archive_round_trip() {
+        assert_eq!(read_archive(&write_archive(&files())), Some(files()));
+        assert_eq!(read_archive(&write_archive(&[])), Some(Vec::new()));
+    }
+
+    #[test]
+    fn truncated_archive_is_rejected() {
+        let archive = write_archive(&files());
+        for len in 0..archive.len() {
+            // cut between two files is a valid(shorter) archive.
+            if let Some(read) = read_archive(&archive[..len]) {
+                assert!(files().starts_with(&read), "misread archive cut at {}", len);
+            }
+        }
+        assert!(read_archive(&archive[..archive.len() - 1]).is_none());
+        assert!(read_archive(b"nope").is_none());
+    }
+
+    #[test]
+    fn oversized_lengths_are_rejected() {
+        let mut archive = BUNDLE_MAGIC.to_vec();
+        archive.extend(1u32.to_le_bytes());
+        archive.push(b'a');
+        archive.extend(u64::MAX.to_le_bytes());
+        archive.extend([0; 16]);
+        assert!(read_archive(&archive).is_none());
+
+        let mut archive = BUNDLE_MAGIC.to_vec();
+        archive.extend(u32::MAX.to_le_bytes());
+        assert!(read_archive(&archive).is_none());
+    }
+}
@@ -0,0 +1,431 @@
+//! saving the world in chunks of a world-space grid, for levels too large to save/load at once. See [`ChunkSettings`].
+//!
+//! [`Save`] entities are saved(with their [`Save`] descendants) to the file of the chunk their root's position is in.
+//! Chunks are loaded around [`ChunkFocus`] entities by [`ChunkedSavePlugin`], or with [`ChunkCommands`]. Entities that
+//! move into another loaded chunk are saved with that chunk from then on.
+//!
+//! Entities get new [`Entity`] ids every time their chunk is loaded, and entities in other chunks may not be loaded at
+//! all. Refer to entities in other chunks with a [`PersistentRef`](resolved through the [`PersistentId`] every saved
+//! entity has, see [`ResolvePersistentRefs`]) rather than an [`Entity`].
+//!
+//! Entities without a position can't be placed in a chunk and are left out. Save them with a regular save, leaving
+//! chunked entities out with a [`SaveProfile`] that is `without::<SaveChunk>()`.
+//!
+//! [`ChunkedSavePlugin`]: crate::plugins::ChunkedSavePlugin
+//! [`ChunkCommands`]: crate::traits::ChunkCommands
+//! [`PersistentRef`]: bevy_synonymize::persistent::PersistentRef
+//! [`PersistentId`]: bevy_synonymize::persistent::PersistentId
+//! [`ResolvePersistentRefs`]: bevy_synonymize::plugins::ResolvePersistentRefs
+//! [`SaveProfile`]: crate::resources::SaveProfile
+
+use std::{collections::HashSet, fs, io};
+
+use bevy_derive::Deref;
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_math::{IVec3, Vec3};
+use bevy_scene::SceneFilter;
+use bevy_transform::prelude::*;
+use moonshine_save::save::Save;
+
+#[cfg(feature = "render")]
+use crate::pipeline::offload_procedural_meshes;
+use crate::{
+    events::{ChunkLoaded, ChunkSaved, LoadError, LoadFailed, SaveError, SaveFailed},
+    pipeline::{encode_scene, extract_scene, read_save, save_input, spawn_save, write_atomic},
+    resources::{LoadOptions, LoadReport, SaveOptions, SavePath},
+    slots::{SaveHeader, SaveMetadata},
+};
+
+/// settings for chunked saves. Can be changed at runtime, though chunks already saved keep the grid they were saved with.
+#[derive(Resource, Clone, Debug)]
+pub struct ChunkSettings {
+    /// folder chunk files are written to.
+    pub folder: String,
+    /// size of a chunk along each axis. Axes that are infinite aren't split, e.g: y for levels laid out on the ground.
+    pub chunk_size: Vec3,
+    /// chunks(along each split axis) around a [`ChunkFocus`] that are kept loaded.
+    pub load_radius: u32,
+    pub save_options: SaveOptions,
+    /// [`LoadOptions::unload`] is ignored. Chunks are loaded alongside everything else.
+    pub load_options: LoadOptions,
+}
+
+impl Default for ChunkSettings {
+    fn default() -> Self {
+        Self {
+            folder: "saves/chunks".to_owned(),
+            chunk_size: Vec3::new(64.0, f32::INFINITY, 64.0),
+            load_radius: 1,
+            save_options: SaveOptions::default(),
+            load_options: LoadOptions::default(),
+        }
+    }
+}
+
+impl ChunkSettings {
+    /// chunk `position` is in.
+    pub fn chunk_of(&self, position: Vec3) -> IVec3 {
+        let axis = |position: f32, size: f32| match size.is_finite() {
+            true => (position / size).floor() as i32,
+            false => 0,
+        };
+        IVec3::new(
+            axis(position.x, self.chunk_size.x),
+            axis(position.y, self.chunk_size.y),
+            axis(position.z, self.chunk_size.z),
+        )
+    }
+
+    /// path of the file `chunk` is saved to.
+    pub fn chunk_path(&self, chunk: IVec3) -> String {
+        format!(
+            "{}/chunk_{}_{}_{}.{}",
+            self.folder.trim_end_matches('/'),
+            chunk.x,
+            chunk.y,
+            chunk.z,
+            self.save_options.format.extension()
+        )
+    }
+
+    /// chunks within [`Self::load_radius`] of `focus`.
+    pub fn chunks_around(&self, focus: Vec3) -> HashSet<IVec3> {
+        let center = self.chunk_of(focus);
+        let radius = self.load_radius as i32;
+        let range = |center: i32, size: f32| match size.is_finite() {
+            true => center - radius..=center + radius,
+            false => 0..=0,
+        };
+        let mut chunks = HashSet::new();
+        for x in range(center.x, self.chunk_size.x) {
+            for y in range(center.y, self.chunk_size.y) {
+                for z in range(center.z, self.chunk_size.z) {
+                    chunks.insert(IVec3::new(x, y, z));
+                }
+            }
+        }
+        chunks
+    }
+}
+
+/// chunks that are currently loaded, including ones that had nothing in them.
+#[derive(Resource, Default, Debug, Deref)]
+pub struct LoadedChunks(pub(crate) HashSet<IVec3>);
+
+/// chunk a loaded [`Save`] entity is saved to.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SaveChunk(pub IVec3);
+
+/// marks entities(e.g: the player/camera) to keep chunks loaded around. See [`ChunkSettings::load_radius`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct ChunkFocus;
+
+/// loads the entities saved in `chunk`. Does nothing if it is already loaded.
+///
+/// Chunks that were never saved load as empty.
+pub fn load_chunk(world: &mut World, chunk: IVec3) -> Result<ChunkLoaded, LoadError> {
+    let settings = world.resource::<ChunkSettings>().clone();
+    let path = settings.chunk_path(chunk);
+    if world.resource::<LoadedChunks>().contains(&chunk) {
+        return Ok(ChunkLoaded {
+            chunk,
+            path,
+            entity_count: 0,
+            report: LoadReport::default(),
+        });
+    }
+
+    let mut options = settings.load_options;
+    options.unload = false;
+    let (entity_map, report) = match read_save(world, &SavePath::File(path.clone())) {
+        Ok((data, format)) => spawn_save(world, format, &data, &options)?,
+        Err(LoadError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+            (EntityHashMap::default(), LoadReport::default())
+        }
+        Err(err) => return Err(err),
+    };
+    for e in entity_map.values() {
+        world.entity_mut(*e).insert(SaveChunk(chunk));
+    }
+    world.resource_mut::<LoadedChunks>().0.insert(chunk);
+    if !report.is_complete() {
+        log::warn!("skipped unknown types while loading chunk {:#}: {:#?}", path, report);
+    }
+
+    Ok(ChunkLoaded {
+        chunk,
+        path,
+        entity_count: entity_map.len(),
+        report,
+    })
+}
+
+/// saves the entities in `chunk`, overwriting its file.
+pub fn save_chunk(world: &mut World, chunk: IVec3) -> Result<ChunkSaved, SaveError> {
+    assign_chunks(world);
+    let settings = world.resource::<ChunkSettings>().clone();
+    let path = settings.chunk_path(chunk);
+    let entities = chunk_entities(world, chunk);
+    let mut input = save_input(world, &entities, &settings.save_options)?;
+    input.resources = SceneFilter::deny_all();
+    #[allow(unused_mut)]
+    let mut scene = extract_scene(world, entities, input);
+    #[cfg(feature = "render")]
+    offload_procedural_meshes(world, &mut scene, &settings.save_options);
+    let metadata = world.get_resource::<SaveMetadata>().cloned().unwrap_or_default();
+    let content = encode_scene(world, &scene, settings.save_options.format)?;
+    let data = SaveHeader::new(&metadata, scene.entities.len()).seal(&content)?;
+    fs::create_dir_all(&settings.folder)?;
+    write_atomic(&path, &data)?;
+
+    Ok(ChunkSaved {
+        chunk,
+        path,
+        entity_count: scene.entities.len(),
+        bytes: data.len(),
+        unloaded: false,
+    })
+}
+
+/// saves `chunk`, then despawns its entities. Nothing is despawned if saving fails.
+pub fn unload_chunk(world: &mut World, chunk: IVec3) -> Result<ChunkSaved, SaveError> {
+    let mut saved = save_chunk(world, chunk)?;
+    for e in chunk_entities(world, chunk) {
+        // may have already been despawned along with a parent.
+        if let Ok(e) = world.get_entity_mut(e) {
+            e.despawn();
+        }
+    }
+    world.resource_mut::<LoadedChunks>().0.remove(&chunk);
+    saved.unloaded = true;
+    Ok(saved)
+}
+
+/// loads chunks around any of `focuses` that aren't loaded, then saves and unloads loaded chunks that aren't around
+/// any of them. Sends [`ChunkLoaded`]/[`ChunkSaved`] for every chunk, or [`LoadFailed`]/[`SaveFailed`].
+pub fn stream_chunks(world: &mut World, focuses: &[Vec3]) {
+    let settings = world.resource::<ChunkSettings>().clone();
+    let wanted = focuses
+        .iter()
+        .flat_map(|n| settings.chunks_around(*n))
+        .collect::<HashSet<_>>();
+    let loaded = world.resource::<LoadedChunks>().0.clone();
+
+    // loaded first, so entities that moved into them are saved with them rather than the chunks they left.
+    for chunk in wanted.difference(&loaded) {
+        load_chunk_with_events(world, *chunk);
+    }
+    for chunk in loaded.difference(&wanted) {
+        save_chunk_with_events(world, *chunk, true);
+    }
+}
+
+/// [`load_chunk`], sending [`ChunkLoaded`]/[`LoadFailed`].
+pub(crate) fn load_chunk_with_events(world: &mut World, chunk: IVec3) {
+    match load_chunk(world, chunk) {
+        Ok(loaded) => {
+            world.send_event(loaded);
+        }
+        Err(error) => {
+            let path = world.resource::<ChunkSettings>().chunk_path(chunk);
+            log::warn!("could not load chunk {:#}: {:#}", path, error);
+            world.send_event(LoadFailed {
+                path: path.into(),
+                error,
+            });
+        }
+    }
+}
+
+/// [`save_chunk`], or [`unload_chunk`] if `unload`, sending [`ChunkSaved`]/[`SaveFailed`].
+pub(crate) fn save_chunk_with_events(world: &mut World, chunk: IVec3, unload: bool) {
+    let result = match unload {
+        true => unload_chunk(world, chunk),
+        false => save_chunk(world, chunk),
+    };
+    match result {
+        Ok(saved) => {
+            world.send_event(saved);
+        }
+        Err(error) => {
+            let path = world.resource::<ChunkSettings>().chunk_path(chunk);
+            log::warn!("could not save chunk {:#}: {:#}", path, error);
+            world.send_event(SaveFailed {
+                path: path.into(),
+                error,
+            });
+        }
+    }
+}
+
+/// entities saved to `chunk`.
+fn chunk_entities(world: &mut World, chunk: IVec3) -> Vec<Entity> {
+    world
+        .query_filtered::<(Entity, &SaveChunk), With<Save>>()
+        .iter(world)
+        .filter(|(_, n)| n.0 == chunk)
+        .map(|(e, _)| e)
+        .collect()
+}
+
+/// tags every [`Save`] entity with the chunk it is saved to: the chunk its root is in if that chunk is loaded, or else
+/// the chunk it was loaded from.
+///
+/// New entities in chunks that aren't loaded get those chunks loaded first, so saving them doesn't overwrite what was
+/// saved there before.
+fn assign_chunks(world: &mut World) {
+    let settings = world.resource::<ChunkSettings>().clone();
+    let entities = world
+        .query_filtered::<Entity, With<Save>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+
+    let mut assigned = Vec::new();
+    let mut unloaded = HashSet::new();
+    for e in entities {
+        let Some(position) = position(world, chunk_root(world, e)) else {
+            continue;
+        };
+        let chunk = settings.chunk_of(position);
+        let current = world.get::<SaveChunk>(e).map(|n| n.0);
+        if current == Some(chunk) {
+            continue;
+        }
+        if world.resource::<LoadedChunks>().contains(&chunk) {
+            assigned.push((e, chunk));
+        } else if current.is_none() {
+            unloaded.insert(chunk);
+            assigned.push((e, chunk));
+        }
+    }
+
+    for chunk in unloaded {
+        if let Err(err) = load_chunk(world, chunk) {
+            log::warn!(
+                "could not load chunk {:#} new entities are in: {:#}. Leaving them out of chunk saves",
+                settings.chunk_path(chunk),
+                err
+            );
+        }
+    }
+    for (e, chunk) in assigned {
+        if world.resource::<LoadedChunks>().contains(&chunk) {
+            world.entity_mut(e).insert(SaveChunk(chunk));
+        }
+    }
+}
+
+/// top-most [`Save`] ancestor of `entity`(or itself). Its position decides the chunk of the whole hierarchy.
+fn chunk_root(world: &World, entity: Entity) -> Entity {
+    let mut root = entity;
+    while let Some(parent) = world.get::<ChildOf>(root).map(|n| n.parent()) {
+        if world.get::<Save>(parent).is_none() {
+            break;
+        }
+        root = parent;
+    }
+    root
+}
+
+/// world-space position of a chunk root. [`GlobalTransform`] is only up to date after transform propagation(not for
+/// entities loaded this frame), so it's only used for roots that have a parent.
+fn position(world: &World, entity: Entity) -> Option<Vec3> {
+    let transform = world.get::<Transform>(entity).map(|n| n.translation);
+    let global = world.get::<GlobalTransform>(entity).map(|n| n.translation());
+    match world.get::<ChildOf>(entity) {
+        Some(_) => global.or(transform),
+        None => transform.or(global),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::Reflect;
+    use bevy_scene::{DynamicEntity, DynamicScene};
+
+    use super::*;
+    use crate::resources::SaveFormat;
+
+    /// refers to an entity that may be in another chunk.
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Target(#[entities] Entity);
+
+    #[test]
+    fn loads_chunk_referring_to_other_chunk() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.resource::<AppTypeRegistry>().write().register::<Target>();
+        let folder = std::env::temp_dir().join(format!("chunk_refs_{}", std::process::id()));
+        let mut settings = ChunkSettings {
+            folder: folder.to_string_lossy().into_owned(),
+            ..Default::default()
+        };
+        settings.save_options.format = SaveFormat::Ron;
+        world.insert_resource(settings.clone());
+        world.init_resource::<LoadedChunks>();
+
+        // entity 1 is saved in another chunk.
+        let scene = DynamicScene {
+            resources: Vec::new(),
+            entities: vec![DynamicEntity {
+                entity: Entity::from_raw(0),
+                components: vec![Box::new(Target(Entity::from_raw(1)))],
+            }],
+        };
+        fs::create_dir_all(&folder).unwrap();
+        let content = encode_scene(&world, &scene, SaveFormat::Ron).unwrap();
+        fs::write(settings.chunk_path(IVec3::ZERO), content).unwrap();
+
+        let loaded = load_chunk(&mut world, IVec3::ZERO).unwrap();
+        assert_eq!(loaded.entity_count, 1);
+        let tagged = world
+            .query::<(&SaveChunk, &Target)>()
+            .iter(&world)
+            .map(|(chunk, _)| chunk.0)
+            .collect::<Vec<_>>();
+        assert_eq!(tagged, [IVec3::ZERO]);
+        assert_eq!(world.query::<&SaveChunk>().iter(&world).count(), 1);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    fn settings() -> ChunkSettings {
+        ChunkSettings {
+            chunk_size: Vec3::new(10.0, f32::INFINITY, 10.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn chunk_of_negative_positions() {
+        let settings = settings();
+        assert_eq!(settings.chunk_of(Vec3::new(0.0, 0.0, 0.0)), IVec3::ZERO);
+        assert_eq!(settings.chunk_of(Vec3::new(9.9, 500.0, 0.1)), IVec3::ZERO);
+        // rounds down, so the chunks either side of 0 don't overlap.
+        assert_eq!(settings.chunk_of(Vec3::new(-0.1, -500.0, -9.9)), IVec3::new(-1, 0, -1));
+        assert_eq!(settings.chunk_of(Vec3::new(-10.0, 0.0, -10.1)), IVec3::new(-1, 0, -2));
+        assert_eq!(settings.chunk_of(Vec3::new(10.0, 0.0, -20.0)), IVec3::new(1, 0, -2));
+    }
+
+    #[test]
+    fn chunks_around_negative_focus() {
+        let settings = settings();
+        let chunks = settings.chunks_around(Vec3::new(-0.5, 3.0, -15.0));
+        let mut expected = HashSet::new();
+        for x in -2..=0 {
+            for z in -3..=-1 {
+                expected.insert(IVec3::new(x, 0, z));
+            }
+        }
+        assert_eq!(chunks, expected);
+
+        let settings = ChunkSettings {
+            load_radius: 0,
+            ..settings
+        };
+        assert_eq!(
+            settings.chunks_around(Vec3::new(-25.0, 0.0, 5.0)),
+            HashSet::from([IVec3::new(-3, 0, 0)])
+        );
+    }
+}
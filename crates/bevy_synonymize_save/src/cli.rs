@@ -0,0 +1,60 @@
+//! setup shared by the tooling binaries. (Requires the `cli` feature)
+
+use bevy_app::App;
+use bevy_asset::AssetPlugin;
+use bevy_ecs::prelude::*;
+use bevy_reflect::{PartialReflect, TypeRegistry, serde::TypedReflectSerializer};
+use bevy_scene::{DynamicScene, ron};
+use bevy_synonymize::plugins::{Synonymize2dPlugin, SynonymizeBasePlugin};
+use bevy_synonymize_physics::plugins::SynonymizePhysicsPlugin;
+
+use crate::{
+    events::LoadError,
+    pipeline::{decode_scene, read_save},
+    plugins::SerializationPlugin,
+    resources::{LoadReport, SavePath},
+};
+
+/// headless app with every synonym of the workspace registered. Only built for its type registry, never run.
+pub fn workspace_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        AssetPlugin::default(),
+        SerializationPlugin,
+        SynonymizeBasePlugin,
+        Synonymize2dPlugin,
+        SynonymizePhysicsPlugin,
+    ));
+    app
+}
+
+/// reads and decodes the save file at `path`, skipping types the workspace doesn't register.
+pub fn read_scene(world: &World, path: &str) -> Result<(DynamicScene, LoadReport), LoadError> {
+    let (data, format) = read_save(world, &SavePath::File(path.to_owned()))?;
+    decode_scene(world, &data, format, true)
+}
+
+/// whether `value` is of the type named `name`, by full or short type path.
+pub fn is_type(value: &dyn PartialReflect, name: &str) -> bool {
+    value
+        .get_represented_type_info()
+        .is_some_and(|n| n.type_path() == name || n.type_path_table().short_path() == name)
+}
+
+/// short type path of `value`, or `?` if it has no type info.
+pub fn short_type_path(value: &dyn PartialReflect) -> &str {
+    value
+        .get_represented_type_info()
+        .map(|n| n.type_path_table().short_path())
+        .unwrap_or("?")
+}
+
+/// `value` as ron, on one line unless `pretty`.
+pub fn to_ron(value: &dyn PartialReflect, registry: &TypeRegistry, pretty: bool) -> String {
+    let serializer = TypedReflectSerializer::new(value, registry);
+    let ron = match pretty {
+        true => ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default()),
+        false => ron::ser::to_string(&serializer),
+    };
+    ron.unwrap_or_else(|err| format!("<{}>", err))
+}
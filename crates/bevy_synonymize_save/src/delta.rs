@@ -0,0 +1,516 @@
+//! incremental saves: deltas with only the entities that changed since the last save, on top of a base save.
+//!
+//! Every world save/load to a file sets the [`DeltaBase`] deltas are made against. Entities in deltas keep the ids
+//! they have in the base, so a chain of deltas can be loaded(or compacted) in a later session.
+//!
+//! Changed entities are written whole, so components removed from an entity are removed in the delta too. Entities
+//! count as changed when a saved component changed, or when they lost one since the last save([`DeltaBase::components`]).
+//!
+//! Deltas are sealed with a [`SaveHeader`] after their [`DeltaHeader`], and checked against it while folding like world
+//! saves are.
+
+use std::{collections::HashSet, fs, path::Path};
+
+use bevy_ecs::{
+    entity::{EntityHashMap, EntityMapper},
+    prelude::*,
+};
+use bevy_scene::DynamicScene;
+use moonshine_save::save::Save;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "render")]
+use crate::pipeline::offload_procedural_meshes;
+use crate::{
+    bundle::is_bundle,
+    events::{LoadCompleted, LoadError, SaveCompleted, SaveError},
+    pipeline::{
+        collect_save_dependencies, decode_scene, encode_scene, extract_scene, read_save, save_input, saved_components,
+        saved_components_changed, spawn_scene, write_atomic,
+    },
+    resources::{
+        DeltaBase, LoadOptions, LoadReport, SaveFormat, SavePath, SaveOptions,
+    },
+    slots::{SaveHeader, SaveMetadata, verify_save},
+};
+
+/// start of the line written before every delta.
+pub const DELTA_PREFIX: &[u8] = b"//save-delta ";
+
+/// what a delta changes besides the entities in it. Written as a json line in front of the delta.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DeltaHeader {
+    /// base save the delta was made against.
+    pub base: String,
+    /// entities(by id in the base, see [`Entity::to_bits`]) despawned since the previous save.
+    pub despawned: Vec<u64>,
+}
+
+impl DeltaHeader {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        let mut bytes = DELTA_PREFIX.to_vec();
+        bytes.extend(serde_json::to_vec(self)?);
+        bytes.push(b'\n');
+        Ok(bytes)
+    }
+}
+
+/// writes the [`Save`] entities whose saved components changed since the last save/load against the [`DeltaBase`],
+/// along with entities despawned since then.
+pub fn save_delta(
+    world: &mut World,
+    path: &str,
+    options: &SaveOptions,
+) -> Result<SaveCompleted, SaveError> {
+    let Some(mut base) = world.remove_resource::<DeltaBase>() else {
+        return Err(SaveError::NoDeltaBase);
+    };
+    let result = write_delta(world, &mut base, path, options);
+    world.insert_resource(base);
+    result
+}
+
+fn write_delta(
+    world: &mut World,
+    base: &mut DeltaBase,
+    path: &str,
+    options: &SaveOptions,
+) -> Result<SaveCompleted, SaveError> {
+    let saved = world
+        .query_filtered::<Entity, With<Save>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    let changed = saved
+        .iter()
+        .copied()
+        .filter(|e| {
+            !base.ids.contains_key(e)
+                || saved_components_changed(world, &[*e], base.last_save)
+                || base
+                    .components
+                    .get(e)
+                    .is_some_and(|n| *n != saved_components(world, *e))
+        })
+        .collect::<Vec<_>>();
+    let still_saved = saved.into_iter().collect::<HashSet<_>>();
+    let mut despawned = Vec::new();
+    base.ids.retain(|e, id| {
+        let kept = still_saved.contains(e);
+        if !kept {
+            despawned.push(id.to_bits());
+        }
+        kept
+    });
+
+    let input = save_input(world, &changed, options)?;
+    let mut scene = extract_scene(world, changed, input);
+    #[cfg(feature = "render")]
+    offload_procedural_meshes(world, &mut scene, options);
+    // entities new since the base get ids past every one in it.
+    let next_index = &mut base.next_index;
+    for entity in &scene.entities {
+        base.ids.entry(entity.entity).or_insert_with(|| {
+            let id = Entity::from_raw(*next_index);
+            *next_index += 1;
+            id
+        });
+    }
+    map_scene_entities(world, &mut scene, &mut base.ids);
+
+    let metadata = world.get_resource::<SaveMetadata>().cloned().unwrap_or_default();
+    let content = encode_scene(world, &scene, options.format)?;
+    let mut data = DeltaHeader {
+        base: base.path.clone(),
+        despawned,
+    }
+    .to_bytes()?;
+    data.extend(SaveHeader::new(&metadata, scene.entities.len()).seal(&content)?);
+    write_atomic(path, &data)?;
+
+    record_saved_components(world, base);
+    base.last_save = world.change_tick();
+    base.deltas.push(path.to_owned());
+    Ok(SaveCompleted {
+        path: SavePath::File(path.to_owned()),
+        entity_count: scene.entities.len(),
+        bytes: data.len(),
+    })
+}
+
+/// remembers the saved components of every entity in `base`, so ones removed later make it into the next delta.
+pub(crate) fn record_saved_components(world: &World, base: &mut DeltaBase) {
+    base.components = base
+        .ids
+        .keys()
+        .filter(|e| world.get_entity(**e).is_ok())
+        .map(|e| (*e, saved_components(world, *e)))
+        .collect();
+}
+
+/// maps entities(and references to entities in components) in `scene` through `ids`.
+fn map_scene_entities(world: &World, scene: &mut DynamicScene, ids: &mut EntityHashMap<Entity>) {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    for entity in &mut scene.entities {
+        entity.entity = ids.get_mapped(entity.entity);
+        for component in &mut entity.components {
+            let Some(reflect_component) = component
+                .get_represented_type_info()
+                .and_then(|n| registry.get(n.type_id()))
+                .and_then(|n| n.data::<ReflectComponent>())
+            else {
+                continue;
+            };
+            match component.try_as_reflect_mut() {
+                Some(component) => reflect_component.map_entities(component, ids),
+                None => log::warn!(
+                    "could not map entities in {:#}, entity references in it may be wrong after loading",
+                    component.reflect_type_path()
+                ),
+            }
+        }
+    }
+}
+
+/// reads the base save at `base` and folds `deltas`(oldest first) on top of it.
+///
+/// Returns the folded scene, the format of the base, and what was skipped if `options.lenient`.
+pub fn fold_deltas(
+    world: &World,
+    base: &str,
+    deltas: &[String],
+    options: &LoadOptions,
+) -> Result<(DynamicScene, SaveFormat, LoadReport), LoadError> {
+    let lenient = options.lenient;
+    let (data, format) = read_save(world, &SavePath::File(base.to_owned()))?;
+    if options.verify {
+        verify_save(&data)?;
+    }
+    let (mut scene, mut report) = decode_scene(world, &data, format, lenient)?;
+
+    for path in deltas {
+        let (header, delta, delta_report) = read_delta(world, path, options)?;
+        if header.base != base {
+            log::warn!(
+                "delta {:#} was made against {:#}, not {:#}. Applying it anyway",
+                path, header.base, base
+            );
+        }
+        report.skipped_components.extend(delta_report.skipped_components);
+        report.skipped_resources.extend(delta_report.skipped_resources);
+        apply_delta(&mut scene, &header, delta);
+    }
+    Ok((scene, format, report))
+}
+
+fn read_delta(
+    world: &World,
+    path: &str,
+    options: &LoadOptions,
+) -> Result<(DeltaHeader, DynamicScene, LoadReport), LoadError> {
+    let data = fs::read(path)?;
+    let (header, body) = split_delta_header(&data)?;
+    if options.verify {
+        verify_save(body)?;
+    }
+    let extension = Path::new(path).extension().and_then(|n| n.to_str());
+    let format = SaveFormat::detect(extension, body);
+    let (scene, report) = decode_scene(world, body, format, options.lenient)?;
+    Ok((header, scene, report))
+}
+
+fn split_delta_header(bytes: &[u8]) -> Result<(DeltaHeader, &[u8]), LoadError> {
+    let line = bytes.strip_prefix(DELTA_PREFIX).ok_or(LoadError::NotADelta)?;
+    let end = line
+        .iter()
+        .position(|n| *n == b'\n')
+        .ok_or(LoadError::NotADelta)?;
+    Ok((serde_json::from_slice(&line[..end])?, &line[end + 1..]))
+}
+
+fn apply_delta(scene: &mut DynamicScene, header: &DeltaHeader, delta: DynamicScene) {
+    let despawned = header
+        .despawned
+        .iter()
+        .filter_map(|n| Entity::try_from_bits(*n).ok())
+        .collect::<HashSet<_>>();
+    scene.entities.retain(|n| !despawned.contains(&n.entity));
+
+    let mut index = scene
+        .entities
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.entity, i))
+        .collect::<EntityHashMap<_>>();
+    for entity in delta.entities {
+        match index.get(&entity.entity) {
+            Some(i) => scene.entities[*i] = entity,
+            None => {
+                index.insert(entity.entity, scene.entities.len());
+                scene.entities.push(entity);
+            }
+        }
+    }
+
+    for resource in delta.resources {
+        let type_path = resource.reflect_type_path().to_owned();
+        match scene
+            .resources
+            .iter_mut()
+            .find(|n| n.reflect_type_path() == type_path)
+        {
+            Some(existing) => *existing = resource,
+            None => scene.resources.push(resource),
+        }
+    }
+}
+
+/// loads the save at `base` with `deltas`(oldest first) applied on top, and makes it the [`DeltaBase`].
+pub fn load_deltas(
+    world: &mut World,
+    base: &str,
+    deltas: &[String],
+    options: &LoadOptions,
+) -> Result<LoadCompleted, LoadError> {
+    let (scene, _, report) = fold_deltas(world, base, deltas, options)?;
+    let entity_map = spawn_scene(world, &scene, options)?;
+    let entity_count = entity_map.len();
+    collect_save_dependencies(world, base.into(), entity_map.values().copied());
+
+    let tick = world.change_tick();
+    let mut delta_base = DeltaBase::loaded(base.to_owned(), entity_map, tick);
+    delta_base.deltas = deltas.to_vec();
+    record_saved_components(world, &mut delta_base);
+    world.insert_resource(delta_base);
+    if !report.is_complete() {
+        log::warn!("skipped unknown types while loading {:#}: {:#?}", base, report);
+    }
+    world.insert_resource(report.clone());
+
+    Ok(LoadCompleted {
+        path: SavePath::File(base.to_owned()),
+        entity_count,
+        report,
+    })
+}
+
+/// folds `deltas` into a full save written over `base`(in the same format), then deletes the deltas.
+///
+/// Ids stay the same, so deltas can keep being made against the compacted base.
+pub fn compact_deltas(
+    world: &mut World,
+    base: &str,
+    deltas: &[String],
+) -> Result<SaveCompleted, SaveError> {
+    if is_bundle(base) {
+        return Err(SaveError::CompactBundle(base.to_owned()));
+    }
+    let (scene, format, _) = fold_deltas(world, base, deltas, &LoadOptions::default())?;
+
+    let metadata = world.get_resource::<SaveMetadata>().cloned().unwrap_or_default();
+    let content = encode_scene(world, &scene, format)?;
+    let data = SaveHeader::new(&metadata, scene.entities.len()).seal(&content)?;
+    write_atomic(base, &data)?;
+
+    for path in deltas {
+        if let Err(err) = fs::remove_file(path) {
+            log::warn!("could not remove compacted delta {:#}: {:#}", path, err);
+        }
+    }
+    if let Some(mut delta_base) = world.get_resource_mut::<DeltaBase>()
+        && delta_base.path == base
+    {
+        delta_base.deltas.retain(|n| !deltas.contains(n));
+    }
+
+    Ok(SaveCompleted {
+        path: SavePath::File(base.to_owned()),
+        entity_count: scene.entities.len(),
+        bytes: data.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::Reflect;
+    use bevy_scene::DynamicEntity;
+
+    use super::*;
+    use crate::{pipeline::save_world, resources::SerializeFilter};
+
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Shield;
+
+    fn entity(id: u32, health: u32) -> DynamicEntity {
+        DynamicEntity {
+            entity: Entity::from_raw(id),
+            components: vec![Box::new(Health(health))],
+        }
+    }
+
+    fn scene(entities: Vec<DynamicEntity>) -> DynamicScene {
+        DynamicScene {
+            resources: Vec::new(),
+            entities,
+        }
+    }
+
+    /// health of every entity in `scene`, by id.
+    fn healths(scene: &DynamicScene) -> Vec<(u32, u32)> {
+        let mut healths = scene
+            .entities
+            .iter()
+            .map(|n| {
+                let health = n.components[0].try_downcast_ref::<Health>().unwrap();
+                (n.entity.index(), health.0)
+            })
+            .collect::<Vec<_>>();
+        healths.sort();
+        healths
+    }
+
+    #[test]
+    fn applies_delta() {
+        let mut base = scene(vec![entity(0, 10), entity(1, 10), entity(2, 10)]);
+        let header = DeltaHeader {
+            base: "base.ron".to_owned(),
+            despawned: vec![Entity::from_raw(1).to_bits()],
+        };
+        apply_delta(&mut base, &header, scene(vec![entity(0, 5), entity(3, 7)]));
+        assert_eq!(healths(&base), [(0, 5), (2, 10), (3, 7)]);
+    }
+
+    #[test]
+    fn later_deltas_win() {
+        let mut base = scene(vec![entity(0, 10)]);
+        let header = DeltaHeader::default();
+        apply_delta(&mut base, &header, scene(vec![entity(0, 5), entity(1, 1)]));
+        apply_delta(&mut base, &header, scene(vec![entity(0, 2)]));
+        let despawn = DeltaHeader {
+            despawned: vec![Entity::from_raw(1).to_bits()],
+            ..Default::default()
+        };
+        apply_delta(&mut base, &despawn, scene(Vec::new()));
+        assert_eq!(healths(&base), [(0, 2)]);
+    }
+
+    #[test]
+    fn folds_deltas_from_files() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.resource::<AppTypeRegistry>().write().register::<Health>();
+
+        let folder = std::env::temp_dir().join(format!("fold_deltas_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let path = |name: &str| folder.join(name).to_string_lossy().into_owned();
+        let base = path("base.ron");
+        let write = |path: &str, header: Option<DeltaHeader>, scene: DynamicScene| {
+            let mut data = header.map(|n| n.to_bytes().unwrap()).unwrap_or_default();
+            data.extend(encode_scene(&world, &scene, SaveFormat::Ron).unwrap());
+            fs::write(path, data).unwrap();
+        };
+
+        write(&base, None, scene(vec![entity(0, 10), entity(1, 10)]));
+        let deltas = vec![path("1.ron"), path("2.ron")];
+        write(
+            &deltas[0],
+            Some(DeltaHeader {
+                base: base.clone(),
+                despawned: Vec::new(),
+            }),
+            scene(vec![entity(0, 5), entity(2, 3)]),
+        );
+        write(
+            &deltas[1],
+            Some(DeltaHeader {
+                base: base.clone(),
+                despawned: vec![Entity::from_raw(0).to_bits()],
+            }),
+            scene(vec![entity(1, 8)]),
+        );
+
+        let (folded, format, report) = fold_deltas(&world, &base, &deltas, &LoadOptions::default()).unwrap();
+        assert_eq!(format, SaveFormat::Ron);
+        assert!(report.is_complete());
+        assert_eq!(healths(&folded), [(1, 8), (2, 3)]);
+
+        assert!(matches!(
+            fold_deltas(&world, &base, &[base.clone()], &LoadOptions::default()),
+            Err(LoadError::NotADelta)
+        ));
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn records_removed_components() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<SerializeFilter>();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<Health>();
+            registry.register::<Shield>();
+        }
+        let e = world.spawn((Save, Health(10), Shield)).id();
+
+        let folder = std::env::temp_dir().join(format!("removed_components_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let base = folder.join("base.ron").to_string_lossy().into_owned();
+        let delta = folder.join("1.ron").to_string_lossy().into_owned();
+        let options = SaveOptions::default();
+        save_world(&mut world, &SavePath::File(base.clone()), &options).unwrap();
+
+        // only a removal, no saved component changed.
+        world.entity_mut(e).remove::<Shield>();
+        let saved = save_delta(&mut world, &delta, &options).unwrap();
+        assert_eq!(saved.entity_count, 1);
+
+        let (folded, _, _) = fold_deltas(&world, &base, &[delta.clone()], &LoadOptions::default()).unwrap();
+        assert_eq!(healths(&folded), [(e.index(), 10)]);
+        assert_eq!(folded.entities[0].components.len(), 1);
+
+        // nothing changed since.
+        let again = folder.join("2.ron").to_string_lossy().into_owned();
+        assert_eq!(save_delta(&mut world, &again, &options).unwrap().entity_count, 0);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn verifies_deltas() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<SerializeFilter>();
+        world.resource::<AppTypeRegistry>().write().register::<Health>();
+        let e = world.spawn((Save, Health(10))).id();
+
+        let folder = std::env::temp_dir().join(format!("verifies_deltas_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let base = folder.join("base.ron").to_string_lossy().into_owned();
+        let delta = folder.join("1.ron").to_string_lossy().into_owned();
+        let options = SaveOptions::default();
+        save_world(&mut world, &SavePath::File(base.clone()), &options).unwrap();
+        world.increment_change_tick();
+        world.entity_mut(e).insert(Health(5));
+        save_delta(&mut world, &delta, &options).unwrap();
+
+        let data = fs::read_to_string(&delta).unwrap();
+        fs::write(&delta, data.replace("(5)", "(6)")).unwrap();
+        assert!(matches!(
+            fold_deltas(&world, &base, &[delta.clone()], &LoadOptions::default()),
+            Err(LoadError::Corrupted(_))
+        ));
+        let unverified = LoadOptions {
+            verify: false,
+            ..Default::default()
+        };
+        let (folded, _, _) = fold_deltas(&world, &base, &[delta], &unverified).unwrap();
+        assert_eq!(healths(&folded), [(e.index(), 6)]);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+}
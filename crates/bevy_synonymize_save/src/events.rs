@@ -0,0 +1,152 @@
+use bevy_ecs::prelude::*;
+use bevy_math::IVec3;
+use bevy_scene::{SceneSpawnError, ron};
+use thiserror::Error;
+
+use crate::resources::{LoadReport, SavePath};
+
+/// sent when a queued save has been written.
+#[derive(Event, Debug, Clone)]
+pub struct SaveCompleted {
+    pub path: SavePath,
+    /// number of entities written to the save.
+    pub entity_count: usize,
+    /// size of the save in bytes.
+    pub bytes: usize,
+}
+
+/// sent when a queued save could not be written.
+#[derive(Event, Debug)]
+pub struct SaveFailed {
+    pub path: SavePath,
+    pub error: SaveError,
+}
+
+/// sent when a queued load has been spawned into the world.
+#[derive(Event, Debug, Clone)]
+pub struct LoadCompleted {
+    pub path: SavePath,
+    /// number of entities spawned from the save.
+    pub entity_count: usize,
+    /// types skipped by a lenient load.
+    pub report: LoadReport,
+}
+
+/// sent when a queued load could not be spawned into the world.
+#[derive(Event, Debug)]
+pub struct LoadFailed {
+    pub path: SavePath,
+    pub error: LoadError,
+}
+
+/// sent once the assets of a loaded save have finished loading, if any of them failed to. See [`SaveDependencies`].
+///
+/// [`SaveDependencies`]: crate::resources::SaveDependencies
+#[derive(Event, Debug, Clone)]
+pub struct MissingAssets {
+    pub save: Option<SavePath>,
+    pub assets: Vec<MissingAsset>,
+}
+
+/// an asset referenced by a loaded save that could not be loaded.
+#[derive(Debug, Clone)]
+pub struct MissingAsset {
+    pub path: String,
+    /// entities left without the asset.
+    pub entities: Vec<Entity>,
+    pub error: String,
+}
+
+/// sent when the watched save was edited on disk and re-applied to the world. See [`crate::hot_reload`].
+#[derive(Event, Debug, Clone)]
+pub struct SaveReloaded {
+    pub path: String,
+    /// number of entities in the world from the save.
+    pub entity_count: usize,
+    /// types skipped while reloading. Reloads are always lenient.
+    pub report: LoadReport,
+}
+
+/// sent when a chunk has been loaded. See [`crate::chunks`].
+#[derive(Event, Debug, Clone)]
+pub struct ChunkLoaded {
+    pub chunk: IVec3,
+    pub path: String,
+    /// number of entities spawned from the chunk. 0 if it was never saved.
+    pub entity_count: usize,
+    pub report: LoadReport,
+}
+
+/// sent when a chunk has been saved, and unloaded if `unloaded`. See [`crate::chunks`].
+#[derive(Event, Debug, Clone)]
+pub struct ChunkSaved {
+    pub chunk: IVec3,
+    pub path: String,
+    pub entity_count: usize,
+    pub bytes: usize,
+    pub unloaded: bool,
+}
+
+/// sent when a prefab has been spawned.
+#[derive(Event, Debug, Clone)]
+pub struct PrefabSpawned {
+    pub path: String,
+    /// root entity of the spawned prefab.
+    pub root: Entity,
+}
+
+/// send to autosave right away(if anything changed), instead of waiting for the autosave interval.
+#[derive(Event, Debug, Clone, Default)]
+pub struct TriggerAutosave;
+
+#[derive(Error, Debug)]
+pub enum SaveError {
+    #[error("could not serialize world: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("could not serialize world to binary: {0}")]
+    Binary(#[from] postcard::Error),
+    #[error("could not serialize world to json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("could not write save: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("prefab root {0} was filtered out by the serialize filter")]
+    PrefabRootFiltered(Entity),
+    #[error("no base save to make a delta against. Save or load the world to a file first")]
+    NoDeltaBase,
+    #[error("could not read saves to compact: {0}")]
+    Compact(#[from] LoadError),
+    #[error("can't compact deltas into save bundle {0}")]
+    CompactBundle(String),
+    #[error("no save profile named {0}. Add it to `SaveProfiles`")]
+    UnknownProfile(String),
+}
+
+#[derive(Error, Debug)]
+pub enum LoadError {
+    #[error("could not read save: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not deserialize save: {0}")]
+    Deserialize(#[from] ron::de::SpannedError),
+    #[error("could not deserialize binary save: {0}")]
+    Binary(#[from] postcard::Error),
+    #[error("could not deserialize json save: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("no world snapshot has been taken")]
+    NoSnapshot,
+    #[error("prefab has no root entity")]
+    NoPrefabRoot,
+    #[error("save is not a delta")]
+    NotADelta,
+    #[error("can't load save bundles without the bundle asset source. Add `SaveBundlePlugin`")]
+    NoBundleSource,
+    #[error("save bundle archive is corrupted")]
+    CorruptBundle,
+    #[error("save bundle has no save in it")]
+    NoBundleSave,
+    #[error("save is corrupted: {0}")]
+    Corrupted(String),
+    #[error("none of the saves to fall back on could be loaded")]
+    NoIntactSave,
+    #[error("could not spawn save into world: {0}")]
+    Spawn(#[from] SceneSpawnError),
+}
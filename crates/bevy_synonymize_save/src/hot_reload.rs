@@ -0,0 +1,305 @@
+//! re-applying the loaded save when its file is edited on disk(e.g: by a designer in a text editor). See [`SaveHotReloadPlugin`].
+//!
+//! Entities are matched by the id they have in the save([`DeltaBase`]), or by [`PersistentId`] once the save is no longer
+//! the delta base, so entities still in the save are updated in place. Entities removed from the save are despawned, and
+//! saved components removed from an entity are removed from it. Deltas loaded on top of the save are re-applied too.
+//!
+//! [`SaveHotReloadPlugin`]: crate::plugins::SaveHotReloadPlugin
+
+use std::{
+    any::TypeId,
+    collections::HashSet,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    path::Path,
+};
+
+use bevy_asset::{Asset, AssetLoader, Handle, LoadContext, io::Reader};
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_reflect::{FromReflect, TypePath};
+use bevy_synonymize::{
+    persistent::PersistentId,
+    resources::{PersistentIds, SynonymTargets},
+};
+
+use crate::{
+    delta::{fold_deltas, record_saved_components},
+    events::{LoadError, SaveReloaded},
+    pipeline::scene_entities,
+    resources::{DeltaBase, LoadOptions, SerializeFilter},
+};
+
+/// name of the asset source watched saves are loaded through.
+pub const SAVE_FILE_SOURCE: &str = "save-file";
+
+/// the save watched for changes by [`SaveHotReloadPlugin`]: the last one loaded from a file under `folder`.
+///
+/// [`SaveHotReloadPlugin`]: crate::plugins::SaveHotReloadPlugin
+#[derive(Resource, Default)]
+pub struct SaveHotReload {
+    /// folder(relative to the working directory) saves are watched in.
+    pub folder: String,
+    pub path: Option<String>,
+    pub(crate) handle: Option<Handle<SaveFileAsset>>,
+    /// hash of the save as the app last loaded/saved it, so saving it from the app doesn't reload it.
+    pub(crate) hash: Option<u64>,
+}
+
+impl SaveHotReload {
+    /// path of the save file at `path` in the watched asset source. `None` if it isn't under [`Self::folder`].
+    pub fn asset_path(&self, path: &str) -> Option<String> {
+        let relative = Path::new(path).strip_prefix(&self.folder).ok()?;
+        Some(format!(
+            "{}://{}",
+            SAVE_FILE_SOURCE,
+            relative.to_string_lossy().replace('\\', "/")
+        ))
+    }
+}
+
+/// a watched save file. Only used to hear about changes to it; the save is read from disk again when reloading.
+#[derive(Asset, TypePath, Debug)]
+pub struct SaveFileAsset;
+
+#[derive(Default)]
+pub struct SaveFileLoader;
+
+impl AssetLoader for SaveFileLoader {
+    type Asset = SaveFileAsset;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load(
+        &self,
+        _reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        Ok(SaveFileAsset)
+    }
+
+    // only loaded by asset type.
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+}
+
+/// hash of the file at `path`. `None` if it can't be read.
+pub fn file_hash(path: &str) -> Option<u64> {
+    let bytes = fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// re-applies the save at `path` to the world, updating entities loaded from it in place.
+///
+/// Entities removed from the save are only despawned while the save is the [`DeltaBase`], as otherwise which entities
+/// came from it is unknown.
+pub fn reload_save(world: &mut World, path: &str) -> Result<SaveReloaded, LoadError> {
+    let base = world
+        .get_resource::<DeltaBase>()
+        .filter(|n| n.path == path)
+        .cloned();
+    let deltas = base.as_ref().map(|n| n.deltas.clone()).unwrap_or_default();
+    // saves are reloaded because they were edited(likely by hand), so they won't match their header.
+    let options = LoadOptions {
+        unload: false,
+        lenient: true,
+        verify: false,
+    };
+    let (scene, _, report) = fold_deltas(world, path, &deltas, &options)?;
+
+    let mut entity_map = match &base {
+        Some(base) => {
+            // id in the save -> entity in the world.
+            let mut entity_map = base
+                .ids
+                .iter()
+                .filter(|(e, _)| world.get_entity(**e).is_ok())
+                .map(|(e, id)| (*id, *e))
+                .collect::<EntityHashMap<_>>();
+            let in_save = scene.entities.iter().map(|n| n.entity).collect::<HashSet<_>>();
+            entity_map.retain(|id, e| {
+                let kept = in_save.contains(id);
+                if !kept && let Ok(e) = world.get_entity_mut(*e) {
+                    e.despawn();
+                }
+                kept
+            });
+            entity_map
+        }
+        None => {
+            log::warn!(
+                "{:#} is no longer the delta base. Matching its entities by persistent id instead",
+                path
+            );
+            let ids = world.get_resource::<PersistentIds>();
+            scene
+                .entities
+                .iter()
+                .filter_map(|n| {
+                    let id = n
+                        .components
+                        .iter()
+                        .find_map(|n| PersistentId::from_reflect(n.as_partial_reflect()))?;
+                    Some((n.entity, ids?.entity(id)?))
+                })
+                .collect()
+        }
+    };
+    for entity in &scene.entities {
+        if let Some(e) = entity_map.get(&entity.entity) {
+            let kept = entity
+                .components
+                .iter()
+                .filter_map(|n| n.get_represented_type_info())
+                .map(|n| n.type_id())
+                .collect::<HashSet<_>>();
+            remove_unsaved_components(world, *e, &kept);
+        }
+    }
+    scene.write_to_world(world, &mut entity_map)?;
+    let entity_map = scene_entities(&scene, &entity_map);
+
+    let tick = world.change_tick();
+    let mut delta_base = DeltaBase::loaded(path.to_owned(), entity_map, tick);
+    delta_base.deltas = deltas;
+    record_saved_components(world, &mut delta_base);
+    let entity_count = delta_base.ids.len();
+    world.insert_resource(delta_base);
+    if let Some(mut hot_reload) = world.get_resource_mut::<SaveHotReload>() {
+        hot_reload.hash = file_hash(path);
+    }
+    if !report.is_complete() {
+        log::warn!("skipped unknown types while reloading {:#}: {:#?}", path, report);
+    }
+    world.insert_resource(report.clone());
+
+    Ok(SaveReloaded {
+        path: path.to_owned(),
+        entity_count,
+        report,
+    })
+}
+
+/// removes components of `entity` that would be saved, but aren't in `kept`.
+fn remove_unsaved_components(world: &mut World, entity: Entity, kept: &HashSet<TypeId>) {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let filter = &world.resource::<SerializeFilter>().components;
+    let synonym_targets = world.get_resource::<SynonymTargets>();
+    let removed = world
+        .entity(entity)
+        .archetype()
+        .components()
+        .filter_map(|n| world.components().get_info(n).and_then(|n| n.type_id()))
+        .filter(|n| {
+            !kept.contains(n)
+                && filter.is_allowed_by_id(*n)
+                && !synonym_targets.is_some_and(|targets| targets.contains_key(n))
+        })
+        .filter_map(|n| registry.get(n).and_then(|n| n.data::<ReflectComponent>()))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut e = world.entity_mut(entity);
+    for reflect_component in removed {
+        reflect_component.remove(&mut e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::Reflect;
+    use bevy_scene::DynamicScene;
+    use moonshine_save::save::Save;
+
+    use super::*;
+    use crate::{
+        pipeline::{decode_scene, encode_scene, save_world},
+        resources::{SaveFormat, SaveOptions, SavePath},
+    };
+
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<SerializeFilter>();
+        world.init_resource::<PersistentIds>();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<Health>();
+            registry.register::<PersistentId>();
+        }
+        world
+    }
+
+    /// healths of every entity with one, sorted.
+    fn healths(world: &mut World) -> Vec<(Entity, u32)> {
+        let mut healths = world
+            .query::<(Entity, &Health)>()
+            .iter(world)
+            .map(|(e, n)| (e, n.0))
+            .collect::<Vec<_>>();
+        healths.sort_by_key(|n| n.1);
+        healths
+    }
+
+    /// saves the world to `name`, and rewrites the save on disk with `edit` applied to it.
+    fn save_and_edit(world: &mut World, name: &str, edit: impl FnOnce(&mut DynamicScene)) -> String {
+        let folder = std::env::temp_dir().join(format!("reload_save_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let path = folder.join(name).to_string_lossy().into_owned();
+        save_world(world, &SavePath::File(path.clone()), &SaveOptions::default()).unwrap();
+        let (mut scene, _) = decode_scene(world, &fs::read(&path).unwrap(), SaveFormat::Ron, false).unwrap();
+        edit(&mut scene);
+        fs::write(&path, encode_scene(world, &scene, SaveFormat::Ron).unwrap()).unwrap();
+        path
+    }
+
+    /// sets the health of the entity with `health` in `scene` to `to`.
+    fn set_health(scene: &mut DynamicScene, health: u32, to: u32) {
+        for component in scene.entities.iter_mut().flat_map(|n| n.components.iter_mut()) {
+            if component.try_downcast_ref::<Health>() == Some(&Health(health)) {
+                *component = Box::new(Health(to));
+            }
+        }
+    }
+
+    #[test]
+    fn updates_delta_base_in_place() {
+        let mut world = world();
+        let kept = world.spawn((Save, Health(11))).id();
+        let removed = world.spawn((Save, Health(22))).id();
+        let path = save_and_edit(&mut world, "delta_base.ron", |scene| {
+            set_health(scene, 11, 33);
+            scene.entities.retain(|n| n.entity != removed);
+        });
+
+        let reloaded = reload_save(&mut world, &path).unwrap();
+        assert_eq!(reloaded.entity_count, 1);
+        assert_eq!(healths(&mut world), [(kept, 33)]);
+        assert!(world.get_entity(removed).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn updates_by_persistent_id() {
+        let mut world = world();
+        let e = world.spawn((Save, Health(11), PersistentId::new())).id();
+        let other = world.spawn((Save, Health(22), PersistentId::new())).id();
+        let path = save_and_edit(&mut world, "persistent_id.ron", |scene| set_health(scene, 11, 33));
+        // no longer the base, e.g: another save was loaded since.
+        world.remove_resource::<DeltaBase>();
+
+        reload_save(&mut world, &path).unwrap();
+        assert_eq!(healths(&mut world), [(other, 22), (e, 33)]);
+        fs::remove_file(path).unwrap();
+    }
+}
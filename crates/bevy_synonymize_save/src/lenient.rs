@@ -0,0 +1,382 @@
+//! scene deserializer that skips unknown types instead of failing. Used by lenient loads.
+//!
+//! Mirrors bevy_scene's [`SceneDeserializer`], with unknown entries read as [`IgnoredAny`].
+//!
+//! [`SceneDeserializer`]: bevy_scene::serde::SceneDeserializer
+
+use std::{cell::RefCell, fmt::Formatter};
+
+use bevy_ecs::{
+    entity::Entity,
+    reflect::{ReflectComponent, ReflectResource},
+};
+use bevy_reflect::{
+    PartialReflect, ReflectFromReflect, TypeRegistry, serde::TypedReflectDeserializer,
+};
+use bevy_scene::{
+    DynamicEntity, DynamicScene,
+    serde::{ENTITY_FIELD_COMPONENTS, ENTITY_STRUCT, SCENE_ENTITIES, SCENE_RESOURCES, SCENE_STRUCT},
+};
+use serde::{
+    Deserialize, Deserializer,
+    de::{DeserializeSeed, Error, IgnoredAny, MapAccess, SeqAccess, Visitor},
+};
+
+use crate::resources::LoadReport;
+
+/// deserializes a [`DynamicScene`], skipping components/resources that are unregistered(or not reflected as one)
+/// and recording them in `report`.
+///
+/// Skipping needs a self describing format(ron/json). Binary saves with unknown types still fail.
+pub struct LenientSceneDeserializer<'a> {
+    pub type_registry: &'a TypeRegistry,
+    pub report: &'a RefCell<LoadReport>,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum SceneField {
+    Resources,
+    Entities,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum EntityField {
+    Components,
+}
+
+#[derive(Clone, Copy)]
+enum EntryKind {
+    Component,
+    Resource,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for LenientSceneDeserializer<'a> {
+    type Value = DynamicScene;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(SCENE_STRUCT, &[SCENE_RESOURCES, SCENE_ENTITIES], self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for LenientSceneDeserializer<'a> {
+    type Value = DynamicScene;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("scene struct")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let resources = seq
+            .next_element_seed(self.entries(EntryKind::Resource))?
+            .ok_or_else(|| Error::missing_field(SCENE_RESOURCES))?;
+        let entities = seq
+            .next_element_seed(LenientEntities(self.entries(EntryKind::Component)))?
+            .ok_or_else(|| Error::missing_field(SCENE_ENTITIES))?;
+
+        Ok(DynamicScene {
+            resources,
+            entities,
+        })
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut resources = None;
+        let mut entities = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                SceneField::Resources => {
+                    if resources.is_some() {
+                        return Err(Error::duplicate_field(SCENE_RESOURCES));
+                    }
+                    resources = Some(map.next_value_seed(self.entries(EntryKind::Resource))?);
+                }
+                SceneField::Entities => {
+                    if entities.is_some() {
+                        return Err(Error::duplicate_field(SCENE_ENTITIES));
+                    }
+                    entities = Some(map.next_value_seed(LenientEntities(
+                        self.entries(EntryKind::Component),
+                    ))?);
+                }
+            }
+        }
+
+        Ok(DynamicScene {
+            resources: resources.ok_or_else(|| Error::missing_field(SCENE_RESOURCES))?,
+            entities: entities.ok_or_else(|| Error::missing_field(SCENE_ENTITIES))?,
+        })
+    }
+}
+
+impl<'a> LenientSceneDeserializer<'a> {
+    fn entries(&self, kind: EntryKind) -> LenientEntries<'a> {
+        LenientEntries {
+            registry: self.type_registry,
+            report: self.report,
+            kind,
+        }
+    }
+}
+
+/// map of entities to their components.
+struct LenientEntities<'a>(LenientEntries<'a>);
+
+impl<'a, 'de> DeserializeSeed<'de> for LenientEntities<'a> {
+    type Value = Vec<DynamicEntity>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for LenientEntities<'a> {
+    type Value = Vec<DynamicEntity>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("map of entities")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entities = Vec::new();
+        while let Some(entity) = map.next_key::<Entity>()? {
+            entities.push(map.next_value_seed(LenientEntity {
+                entity,
+                components: self.0,
+            })?);
+        }
+        Ok(entities)
+    }
+}
+
+/// a single entity and its components.
+struct LenientEntity<'a> {
+    entity: Entity,
+    components: LenientEntries<'a>,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for LenientEntity<'a> {
+    type Value = DynamicEntity;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(ENTITY_STRUCT, &[ENTITY_FIELD_COMPONENTS], self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for LenientEntity<'a> {
+    type Value = DynamicEntity;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("entities")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let components = seq
+            .next_element_seed(self.components)?
+            .ok_or_else(|| Error::missing_field(ENTITY_FIELD_COMPONENTS))?;
+        Ok(DynamicEntity {
+            entity: self.entity,
+            components,
+        })
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut components = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                EntityField::Components => {
+                    if components.is_some() {
+                        return Err(Error::duplicate_field(ENTITY_FIELD_COMPONENTS));
+                    }
+                    components = Some(map.next_value_seed(self.components)?);
+                }
+            }
+        }
+        Ok(DynamicEntity {
+            entity: self.entity,
+            components: components.ok_or_else(|| Error::missing_field(ENTITY_FIELD_COMPONENTS))?,
+        })
+    }
+}
+
+/// map of type paths to reflected values. Unknown type paths are skipped.
+#[derive(Clone, Copy)]
+struct LenientEntries<'a> {
+    registry: &'a TypeRegistry,
+    report: &'a RefCell<LoadReport>,
+    kind: EntryKind,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for LenientEntries<'a> {
+    type Value = Vec<Box<dyn PartialReflect>>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for LenientEntries<'a> {
+    type Value = Vec<Box<dyn PartialReflect>>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("map of reflect types")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(type_path) = map.next_key::<String>()? {
+            let registration = self
+                .registry
+                .get_with_type_path(&type_path)
+                .filter(|n| match self.kind {
+                    EntryKind::Component => n.data::<ReflectComponent>().is_some(),
+                    EntryKind::Resource => n.data::<ReflectResource>().is_some(),
+                });
+            let Some(registration) = registration else {
+                map.next_value::<IgnoredAny>()?;
+                let mut report = self.report.borrow_mut();
+                match self.kind {
+                    EntryKind::Component => {
+                        *report.skipped_components.entry(type_path).or_insert(0) += 1
+                    }
+                    EntryKind::Resource => report.skipped_resources.push(type_path),
+                }
+                continue;
+            };
+
+            let value =
+                map.next_value_seed(TypedReflectDeserializer::new(registration, self.registry))?;
+            // same as bevy_scene, prefer the concrete type over a dynamic one.
+            let value = registration
+                .data::<ReflectFromReflect>()
+                .and_then(|fr| fr.from_reflect(value.as_partial_reflect()))
+                .map(PartialReflect::into_partial_reflect)
+                .unwrap_or(value);
+            entries.push(value);
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{entity::EntityHashMap, prelude::*};
+    use bevy_reflect::Reflect;
+    use bevy_scene::DynamicSceneBuilder;
+
+    use crate::{
+        pipeline::{decode_scene, encode_scene},
+        resources::SaveFormat,
+    };
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    /// left out of the registry of the loading app.
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Removed(u32);
+
+    #[derive(Resource, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Resource)]
+    struct Gold(u32);
+
+    /// left out of the registry of the loading app.
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct RemovedSettings(u32);
+
+    fn world(known_only: bool) -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<Health>();
+            registry.register::<Gold>();
+            if !known_only {
+                registry.register::<Removed>();
+                registry.register::<RemovedSettings>();
+            }
+        }
+        world
+    }
+
+    /// saves entities/resources with types the loading app doesn't know, and loads them leniently.
+    fn skips_unknown_types(format: SaveFormat) {
+        let mut saving = world(false);
+        saving.insert_resource(Gold(7));
+        saving.insert_resource(RemovedSettings(1));
+        saving.spawn((Health(10), Removed(1)));
+        saving.spawn(Removed(2));
+        saving.spawn(Health(3));
+        let scene = DynamicSceneBuilder::from_world(&saving)
+            .extract_entities(saving.iter_entities().map(|n| n.id()))
+            .extract_resources()
+            .build();
+        let save = encode_scene(&saving, &scene, format).unwrap();
+
+        let mut loading = world(true);
+        assert!(decode_scene(&loading, &save, format, false).is_err());
+        let (scene, report) = decode_scene(&loading, &save, format, true).unwrap();
+        assert_eq!(report.skipped_components.len(), 1);
+        assert_eq!(report.skipped_components.values().sum::<usize>(), 2);
+        assert!(report.skipped_components.keys().all(|n| n.ends_with("Removed")));
+        assert_eq!(report.skipped_resources.len(), 1);
+        assert!(report.skipped_resources[0].ends_with("RemovedSettings"));
+
+        // the rest still spawns.
+        scene.write_to_world(&mut loading, &mut EntityHashMap::default()).unwrap();
+        assert_eq!(loading.resource::<Gold>(), &Gold(7));
+        let mut healths = loading
+            .query::<&Health>()
+            .iter(&loading)
+            .map(|n| n.0)
+            .collect::<Vec<_>>();
+        healths.sort();
+        assert_eq!(healths, [3, 10]);
+        assert_eq!(loading.query::<Entity>().iter(&loading).count(), 3);
+    }
+
+    #[test]
+    fn skips_unknown_types_ron() {
+        skips_unknown_types(SaveFormat::Ron);
+    }
+
+    #[test]
+    fn skips_unknown_types_json() {
+        skips_unknown_types(SaveFormat::Json);
+    }
+}
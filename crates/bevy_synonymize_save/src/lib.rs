@@ -1,3 +1,20 @@
 pub mod plugins;
-mod systems;
-pub mod resources;
\ No newline at end of file
+pub mod systems;
+pub mod resources;
+pub mod events;
+pub mod pipeline;
+pub mod lenient;
+pub mod prefab;
+pub mod delta;
+pub mod bundle;
+pub mod undo;
+pub mod hot_reload;
+pub mod chunks;
+pub mod autosave;
+pub mod slots;
+pub mod schema;
+pub mod traits;
+#[cfg(feature = "render")]
+pub mod mesh_file;
+#[cfg(feature = "cli")]
+pub mod cli;
@@ -0,0 +1,292 @@
+//! compact binary mesh format for procedural meshes offloaded from saves.
+//!
+//! layout(little endian):
+//! ```text
+//! magic: [u8; 4] = MESH_FILE_MAGIC
+//! version: u8
+//! topology: u8
+//! attribute_count: u32
+//!     name_len: u32, name: [u8; name_len]
+//!     format: u8
+//!     byte_len: u32, data: [u8; byte_len]
+//! indices: u8 (0 = none, 1 = u16, 2 = u32)
+//!     byte_len: u32, data: [u8; byte_len]
+//! ```
+
+use bevy_asset::{AssetLoader, LoadContext, io::Reader};
+use bevy_render::{
+    mesh::{Indices, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues, VertexFormat},
+    prelude::*,
+    render_asset::RenderAssetUsages,
+};
+use log::warn;
+use thiserror::Error;
+
+pub const MESH_FILE_MAGIC: [u8; 4] = *b"BSXM";
+pub const MESH_FILE_VERSION: u8 = 1;
+pub const MESH_FILE_EXTENSION: &str = "mesh";
+
+/// vertex attributes that can be written to/read from mesh files.
+const KNOWN_ATTRIBUTES: [MeshVertexAttribute; 8] = [
+    Mesh::ATTRIBUTE_POSITION,
+    Mesh::ATTRIBUTE_NORMAL,
+    Mesh::ATTRIBUTE_UV_0,
+    Mesh::ATTRIBUTE_UV_1,
+    Mesh::ATTRIBUTE_TANGENT,
+    Mesh::ATTRIBUTE_COLOR,
+    Mesh::ATTRIBUTE_JOINT_WEIGHT,
+    Mesh::ATTRIBUTE_JOINT_INDEX,
+];
+
+#[derive(Error, Debug)]
+pub enum MeshFileError {
+    #[error("not a mesh file(bad magic bytes)")]
+    Magic,
+    #[error("unsupported mesh file version {0}")]
+    Version(u8),
+    #[error("mesh file ended unexpectedly")]
+    Truncated,
+    #[error("unknown {0} tag {1} in mesh file")]
+    UnknownTag(&'static str, u8),
+    #[error("could not read mesh file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// encodes a [`Mesh`] into the mesh file format.
+///
+/// Custom vertex attributes and uncommon vertex formats are skipped.
+pub fn mesh_to_bytes(mesh: &Mesh) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&MESH_FILE_MAGIC);
+    bytes.push(MESH_FILE_VERSION);
+    bytes.push(topology_tag(mesh.primitive_topology()));
+
+    let attributes = mesh
+        .attributes()
+        .filter_map(|(attribute, values)| {
+            if !KNOWN_ATTRIBUTES.iter().any(|n| n.id == attribute.id) {
+                warn!(
+                    "custom vertex attribute {:#} can not be written to mesh files. Skipping",
+                    attribute.name
+                );
+                return None;
+            }
+            let Some(tag) = values_tag(values) else {
+                warn!(
+                    "vertex format of {:#} can not be written to mesh files. Skipping",
+                    attribute.name
+                );
+                return None;
+            };
+            Some((attribute, tag, values))
+        })
+        .collect::<Vec<_>>();
+
+    bytes.extend_from_slice(&(attributes.len() as u32).to_le_bytes());
+    for (attribute, tag, values) in attributes {
+        write_block(&mut bytes, attribute.name.as_bytes());
+        bytes.push(tag);
+        write_block(&mut bytes, values.get_bytes());
+    }
+
+    match mesh.indices() {
+        None => bytes.push(0),
+        Some(Indices::U16(indices)) => {
+            bytes.push(1);
+            write_block(&mut bytes, bytemuck::cast_slice(indices));
+        }
+        Some(Indices::U32(indices)) => {
+            bytes.push(2);
+            write_block(&mut bytes, bytemuck::cast_slice(indices));
+        }
+    }
+    bytes
+}
+
+/// decodes a [`Mesh`] from the mesh file format.
+pub fn mesh_from_bytes(bytes: &[u8]) -> Result<Mesh, MeshFileError> {
+    let mut cursor = Cursor(bytes);
+
+    if cursor.take(4)? != MESH_FILE_MAGIC {
+        return Err(MeshFileError::Magic);
+    }
+    let version = cursor.u8()?;
+    if version != MESH_FILE_VERSION {
+        return Err(MeshFileError::Version(version));
+    }
+    let topology = match cursor.u8()? {
+        0 => PrimitiveTopology::PointList,
+        1 => PrimitiveTopology::LineList,
+        2 => PrimitiveTopology::LineStrip,
+        3 => PrimitiveTopology::TriangleList,
+        4 => PrimitiveTopology::TriangleStrip,
+        n => return Err(MeshFileError::UnknownTag("topology", n)),
+    };
+    let mut mesh = Mesh::new(topology, RenderAssetUsages::default());
+
+    let attribute_count = cursor.u32()?;
+    for _ in 0..attribute_count {
+        let name = cursor.block()?;
+        let tag = cursor.u8()?;
+        let data = cursor.block()?;
+        let values = match tag {
+            0 => VertexAttributeValues::Float32(bytemuck::pod_collect_to_vec(data)),
+            1 => VertexAttributeValues::Float32x2(bytemuck::pod_collect_to_vec(data)),
+            2 => VertexAttributeValues::Float32x3(bytemuck::pod_collect_to_vec(data)),
+            3 => VertexAttributeValues::Float32x4(bytemuck::pod_collect_to_vec(data)),
+            4 => VertexAttributeValues::Uint32(bytemuck::pod_collect_to_vec(data)),
+            5 => VertexAttributeValues::Uint16x4(bytemuck::pod_collect_to_vec(data)),
+            6 => VertexAttributeValues::Unorm8x4(bytemuck::pod_collect_to_vec(data)),
+            n => return Err(MeshFileError::UnknownTag("vertex format", n)),
+        };
+        let Some(attribute) = KNOWN_ATTRIBUTES.iter().find(|n| n.name.as_bytes() == name) else {
+            warn!(
+                "unknown vertex attribute {:#} in mesh file. Skipping",
+                String::from_utf8_lossy(name)
+            );
+            continue;
+        };
+        if VertexFormat::from(&values) != attribute.format {
+            warn!(
+                "vertex attribute {:#} has the wrong format in mesh file. Skipping",
+                attribute.name
+            );
+            continue;
+        }
+        mesh.insert_attribute(*attribute, values);
+    }
+
+    match cursor.u8()? {
+        0 => {}
+        1 => mesh.insert_indices(Indices::U16(bytemuck::pod_collect_to_vec(cursor.block()?))),
+        2 => mesh.insert_indices(Indices::U32(bytemuck::pod_collect_to_vec(cursor.block()?))),
+        n => return Err(MeshFileError::UnknownTag("indices", n)),
+    }
+    Ok(mesh)
+}
+
+/// loads meshes written by [`mesh_to_bytes`] through the asset server.
+#[derive(Default)]
+pub struct MeshFileLoader;
+
+impl AssetLoader for MeshFileLoader {
+    type Asset = Mesh;
+    type Settings = ();
+    type Error = MeshFileError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        mesh_from_bytes(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[MESH_FILE_EXTENSION]
+    }
+}
+
+fn topology_tag(topology: PrimitiveTopology) -> u8 {
+    match topology {
+        PrimitiveTopology::PointList => 0,
+        PrimitiveTopology::LineList => 1,
+        PrimitiveTopology::LineStrip => 2,
+        PrimitiveTopology::TriangleList => 3,
+        PrimitiveTopology::TriangleStrip => 4,
+    }
+}
+
+fn values_tag(values: &VertexAttributeValues) -> Option<u8> {
+    match values {
+        VertexAttributeValues::Float32(_) => Some(0),
+        VertexAttributeValues::Float32x2(_) => Some(1),
+        VertexAttributeValues::Float32x3(_) => Some(2),
+        VertexAttributeValues::Float32x4(_) => Some(3),
+        VertexAttributeValues::Uint32(_) => Some(4),
+        VertexAttributeValues::Uint16x4(_) => Some(5),
+        VertexAttributeValues::Unorm8x4(_) => Some(6),
+        _ => None,
+    }
+}
+
+fn write_block(bytes: &mut Vec<u8>, block: &[u8]) {
+    bytes.extend_from_slice(&(block.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(block);
+}
+
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MeshFileError> {
+        if self.0.len() < len {
+            return Err(MeshFileError::Truncated);
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, MeshFileError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, MeshFileError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn block(&mut self) -> Result<&'a [u8], MeshFileError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::prelude::*;
+
+    use super::*;
+
+    fn cuboid() -> Mesh {
+        Mesh::from(Cuboid::new(1.0, 2.0, 3.0))
+    }
+
+    #[test]
+    fn mesh_round_trip() {
+        let mesh = cuboid().with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0, 0.5, 0.0, 1.0]; 24]);
+        let read = mesh_from_bytes(&mesh_to_bytes(&mesh)).unwrap();
+
+        assert_eq!(read.primitive_topology(), mesh.primitive_topology());
+        assert_eq!(read.attributes().count(), mesh.attributes().count());
+        for (attribute, values) in mesh.attributes() {
+            let read = read.attribute(attribute.id).unwrap();
+            assert_eq!(read.get_bytes(), values.get_bytes(), "{} differs", attribute.name);
+        }
+        assert_eq!(
+            read.indices().unwrap().iter().collect::<Vec<_>>(),
+            mesh.indices().unwrap().iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn mesh_without_indices_round_trip() {
+        let mesh = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]]);
+        let read = mesh_from_bytes(&mesh_to_bytes(&mesh)).unwrap();
+        assert_eq!(read.primitive_topology(), PrimitiveTopology::LineList);
+        assert!(read.indices().is_none());
+        assert_eq!(read.count_vertices(), 2);
+    }
+
+    #[test]
+    fn truncated_mesh_file_fails() {
+        let bytes = mesh_to_bytes(&cuboid());
+        for len in 0..bytes.len() {
+            assert!(mesh_from_bytes(&bytes[..len]).is_err(), "read mesh file cut at {}", len);
+        }
+        assert!(matches!(mesh_from_bytes(b"nope, not a mesh"), Err(MeshFileError::Magic)));
+    }
+}
@@ -0,0 +1,761 @@
+//! conversion between the world and saves(files or in memory), used by queued save/load requests.
+
+use std::{cell::RefCell, collections::HashSet, fs, io::{self, Write}, path::Path};
+
+use bevy_ecs::{
+    component::{ComponentId, Tick},
+    entity::EntityHashMap,
+    prelude::*,
+};
+use bevy_reflect::TypeRegistry;
+use bevy_scene::{
+    DynamicScene, DynamicSceneBuilder, ron,
+    serde::{SceneDeserializer, SceneSerializer},
+};
+use bevy_synonymize::{resources::SynonymTargets, traits::ReflectAssetPath};
+use moonshine_save::{
+    load::Unload,
+    save::{EntityFilter, Save, SaveInput},
+};
+use serde::{Deserializer, de::DeserializeSeed};
+
+use crate::{
+    bundle::{BUNDLE_MAGIC, read_bundle, read_bundle_archive},
+    delta::record_saved_components,
+    events::{LoadCompleted, LoadError, SaveCompleted, SaveError},
+    lenient::LenientSceneDeserializer,
+    resources::{
+        DeltaBase, LoadOptions, LoadReport, SaveDependencies, SaveFormat, SaveOptions, SavePath, SaveProfiles,
+        SerializeFilter,
+        WorldSnapshot,
+    },
+    slots::{SaveHeader, SaveMetadata, split_header, verify_file, verify_save},
+};
+#[cfg(feature = "render")]
+use {
+    crate::{
+        mesh_file::{MESH_FILE_EXTENSION, mesh_to_bytes},
+        resources::ProceduralMeshes,
+        slots::content_hash,
+    },
+    bevy_synonymize::prelude::mesh::{Mesh3dFlag, MeshWrapper},
+    std::any::TypeId,
+};
+
+/// builds a scene of every [`Save`] entity(and resource) let through by the save profile in `options`.
+pub fn extract_save_scene(world: &mut World, options: &SaveOptions) -> Result<DynamicScene, SaveError> {
+    let entities = world
+        .query_filtered::<Entity, With<Save>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    let input = save_input(world, &entities, options)?;
+    Ok(extract_scene(world, entities, input))
+}
+
+/// what to save of `entities`: what [`SaveOptions::profile`] lets through, or the [`SerializeFilter`] without one.
+pub fn save_input(world: &World, entities: &[Entity], options: &SaveOptions) -> Result<SaveInput, SaveError> {
+    let Some(name) = &options.profile else {
+        return Ok(world.resource::<SerializeFilter>().0.clone());
+    };
+    let Some(profile) = world.get_resource::<SaveProfiles>().and_then(|n| n.get(name)) else {
+        return Err(SaveError::UnknownProfile(name.clone()));
+    };
+    Ok(SaveInput {
+        components: profile.components.clone(),
+        resources: profile.resources.clone(),
+        entities: EntityFilter::allow(
+            entities
+                .iter()
+                .copied()
+                .filter(|e| profile.entities.iter().all(|n| n.matches(world, *e))),
+        ),
+    })
+}
+
+/// builds a scene of the given `entities`(and resources) let through by `input`.
+///
+/// Synonym targets([`SynonymTargets`]) are always skipped, as their synonyms are saved instead.
+pub fn extract_scene(
+    world: &World,
+    entities: impl IntoIterator<Item = Entity>,
+    input: SaveInput,
+) -> DynamicScene {
+    let mut components = input.components;
+    if let Some(targets) = world.get_resource::<SynonymTargets>() {
+        for target in targets.keys() {
+            components = components.deny_by_id(*target);
+        }
+    }
+
+    let entities = entities.into_iter().filter(|e| match &input.entities {
+        EntityFilter::Any => true,
+        EntityFilter::Allow(allowed) => allowed.contains(e),
+        EntityFilter::Block(blocked) => !blocked.contains(e),
+    });
+
+    DynamicSceneBuilder::from_world(world)
+        .with_component_filter(components)
+        .with_resource_filter(input.resources)
+        .extract_entities(entities)
+        .extract_resources()
+        .build()
+}
+
+/// encodes a scene into `format`.
+pub fn encode_scene(
+    world: &World,
+    scene: &DynamicScene,
+    format: SaveFormat,
+) -> Result<Vec<u8>, SaveError> {
+    let registry = world.resource::<AppTypeRegistry>().read();
+    Ok(match format {
+        SaveFormat::Ron => scene.serialize(&registry)?.into_bytes(),
+        SaveFormat::Binary => {
+            let mut data = SaveFormat::BINARY_MAGIC.to_vec();
+            data.extend(postcard::to_allocvec(&SceneSerializer::new(scene, &registry))?);
+            data
+        }
+        SaveFormat::Json => serde_json::to_vec_pretty(&SceneSerializer::new(scene, &registry))?,
+    })
+}
+
+/// decodes a scene encoded in `format`. If `lenient`, unknown types are skipped and listed in the returned report.
+pub fn decode_scene(
+    world: &World,
+    bytes: &[u8],
+    format: SaveFormat,
+    lenient: bool,
+) -> Result<(DynamicScene, LoadReport), LoadError> {
+    let (_, bytes) = split_header(bytes);
+    let report = RefCell::new(LoadReport::default());
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let scene = match format {
+        SaveFormat::Ron => {
+            let mut deserializer = ron::de::Deserializer::from_bytes(bytes)?;
+            deserialize_scene(&registry, lenient, &report, &mut deserializer)
+                .map_err(|e| deserializer.span_error(e))?
+        }
+        SaveFormat::Binary => {
+            let body = bytes
+                .strip_prefix(&SaveFormat::BINARY_MAGIC)
+                .ok_or_else(|| LoadError::Corrupted("binary save doesn't start with its magic bytes".to_owned()))?;
+            deserialize_scene(
+                &registry,
+                lenient,
+                &report,
+                &mut postcard::Deserializer::from_bytes(body),
+            )?
+        }
+        SaveFormat::Json => deserialize_scene(
+            &registry,
+            lenient,
+            &report,
+            &mut serde_json::Deserializer::from_slice(bytes),
+        )?,
+    };
+    Ok((scene, report.into_inner()))
+}
+
+fn deserialize_scene<'de, D: Deserializer<'de>>(
+    registry: &TypeRegistry,
+    lenient: bool,
+    report: &RefCell<LoadReport>,
+    deserializer: D,
+) -> Result<DynamicScene, D::Error> {
+    if lenient {
+        LenientSceneDeserializer {
+            type_registry: registry,
+            report,
+        }
+        .deserialize(deserializer)
+    } else {
+        SceneDeserializer {
+            type_registry: registry,
+        }
+        .deserialize(deserializer)
+    }
+}
+
+/// reads the save at `path`(or out of the bundle at `path`), and detects its format.
+pub fn read_save(world: &World, path: &SavePath) -> Result<(Vec<u8>, SaveFormat), LoadError> {
+    let (data, extension) = match path {
+        SavePath::File(file) if Path::new(file).is_dir() => read_bundle(world, file)?,
+        SavePath::File(file) => {
+            // read once, and tell bundle archives apart by their magic in memory.
+            let data = fs::read(file)?;
+            match data.starts_with(&BUNDLE_MAGIC) {
+                true => read_bundle_archive(world, file, &data)?,
+                false => (
+                    data,
+                    Path::new(file)
+                        .extension()
+                        .and_then(|n| n.to_str())
+                        .map(|n| n.to_owned()),
+                ),
+            }
+        }
+        SavePath::Snapshot => (
+            world
+                .get_resource::<WorldSnapshot>()
+                .ok_or(LoadError::NoSnapshot)?
+                .0
+                .clone(),
+            None,
+        ),
+    };
+    let format = SaveFormat::detect(extension.as_deref(), &data);
+    Ok((data, format))
+}
+
+/// saves the world to a byte buffer, in the same format as save files.
+pub fn save_world_to_bytes(world: &mut World, options: &SaveOptions) -> Result<Vec<u8>, SaveError> {
+    Ok(serialize_world(world, options)?.0)
+}
+
+/// loads a byte buffer made by [`save_world_to_bytes`](or read from a save file) into the world.
+///
+/// Returns the number of entities spawned, and what was skipped if the load is lenient.
+/// The save is fully parsed before anything in the world is unloaded, so a bad save leaves the world untouched.
+pub fn load_world_from_bytes(
+    world: &mut World,
+    bytes: &[u8],
+    options: &LoadOptions,
+) -> Result<(usize, LoadReport), LoadError> {
+    let (entity_map, report) = spawn_save(world, SaveFormat::detect(None, bytes), bytes, options)?;
+    Ok((entity_map.len(), report))
+}
+
+/// spawns a save, returning which entity in the world each entity in the save was spawned as.
+pub(crate) fn spawn_save(
+    world: &mut World,
+    format: SaveFormat,
+    bytes: &[u8],
+    options: &LoadOptions,
+) -> Result<(EntityHashMap<Entity>, LoadReport), LoadError> {
+    if options.verify {
+        verify_save(bytes)?;
+    }
+    let (scene, report) = decode_scene(world, bytes, format, options.lenient)?;
+    let entity_map = spawn_scene(world, &scene, options)?;
+    Ok((entity_map, report))
+}
+
+/// spawns a decoded save, unloading the world first if `options.unload`.
+pub(crate) fn spawn_scene(
+    world: &mut World,
+    scene: &DynamicScene,
+    options: &LoadOptions,
+) -> Result<EntityHashMap<Entity>, LoadError> {
+    if options.unload {
+        unload_world(world);
+    }
+    let mut entity_map = EntityHashMap::default();
+    scene.write_to_world(world, &mut entity_map)?;
+    Ok(scene_entities(scene, &entity_map))
+}
+
+/// the entities of `scene` in `entity_map`. [`DynamicScene::write_to_world`] also maps entities the scene refers to
+/// but doesn't contain(e.g: a parent that wasn't saved, or an entity in another chunk) to dead placeholders, which are
+/// left out.
+pub(crate) fn scene_entities(scene: &DynamicScene, entity_map: &EntityHashMap<Entity>) -> EntityHashMap<Entity> {
+    scene
+        .entities
+        .iter()
+        .filter_map(|n| Some((n.entity, *entity_map.get(&n.entity)?)))
+        .collect()
+}
+
+/// saves the world to a file or the [`WorldSnapshot`].
+pub fn save_world(
+    world: &mut World,
+    path: &SavePath,
+    options: &SaveOptions,
+) -> Result<SaveCompleted, SaveError> {
+    let (data, saved) = serialize_world(world, options)?;
+    let bytes = data.len();
+    let entity_count = saved.len();
+    match path {
+        SavePath::File(file) => {
+            write_atomic(file, &data)?;
+            if options.delta_base {
+                // entities keep their ids in the base.
+                let ids = saved.into_iter().map(|e| (e, e)).collect();
+                let tick = world.change_tick();
+                let mut base = DeltaBase::new(file.clone(), ids, tick);
+                record_saved_components(world, &mut base);
+                world.insert_resource(base);
+            }
+        }
+        SavePath::Snapshot => world.insert_resource(WorldSnapshot(data)),
+    }
+
+    Ok(SaveCompleted {
+        path: path.clone(),
+        entity_count,
+        bytes,
+    })
+}
+
+/// loads a save file or the [`WorldSnapshot`] into the world.
+pub fn load_world(
+    world: &mut World,
+    path: &SavePath,
+    options: &LoadOptions,
+) -> Result<LoadCompleted, LoadError> {
+    let (data, format) = read_save(world, path)?;
+    let (entity_map, report) = spawn_save(world, format, &data, options)?;
+    let entity_count = entity_map.len();
+    collect_save_dependencies(world, path.clone(), entity_map.values().copied());
+    if let SavePath::File(file) = path {
+        let tick = world.change_tick();
+        let mut base = DeltaBase::loaded(file.clone(), entity_map, tick);
+        record_saved_components(world, &mut base);
+        world.insert_resource(base);
+    }
+    if !report.is_complete() {
+        log::warn!("skipped unknown types while loading {:#}: {:#?}", path, report);
+    }
+    world.insert_resource(report.clone());
+
+    Ok(LoadCompleted {
+        path: path.clone(),
+        entity_count,
+        report,
+    })
+}
+
+/// loads the first save file in `paths` that isn't corrupted, e.g: the newest autosave that was fully written.
+///
+/// Corrupted and missing saves are skipped. Other errors fail the load, as older saves would likely fail the same way.
+pub fn load_first_intact(
+    world: &mut World,
+    paths: &[String],
+    options: &LoadOptions,
+) -> Result<LoadCompleted, LoadError> {
+    for path in paths {
+        // checked before loading, so the world isn't unloaded for a save that turns out to be corrupted.
+        match verify_file(path) {
+            Ok(()) => return load_world(world, &SavePath::File(path.clone()), options),
+            Err(err @ (LoadError::Corrupted(_) | LoadError::Io(_))) => {
+                log::warn!("skipping save {:#}: {:#}. Falling back to an older one", path, err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Err(LoadError::NoIntactSave)
+}
+
+/// replaces the [`SaveDependencies`] with the asset paths referenced by asset synonyms on `entities`.
+pub fn collect_save_dependencies(
+    world: &mut World,
+    save: SavePath,
+    entities: impl IntoIterator<Item = Entity>,
+) {
+    let mut dependencies = SaveDependencies {
+        save: Some(save),
+        ..Default::default()
+    };
+    let registry = world.resource::<AppTypeRegistry>().read();
+    for e in entities {
+        let Ok(entity) = world.get_entity(e) else {
+            continue;
+        };
+        for component in entity.archetype().components() {
+            let Some(registration) = world
+                .components()
+                .get_info(component)
+                .and_then(|n| n.type_id())
+                .and_then(|n| registry.get(n))
+            else {
+                continue;
+            };
+            let (Some(asset_path), Some(reflect_component)) = (
+                registration.data::<ReflectAssetPath>(),
+                registration.data::<ReflectComponent>(),
+            ) else {
+                continue;
+            };
+            let Some(path) = reflect_component
+                .reflect(entity)
+                .and_then(|n| (asset_path.get)(n))
+            else {
+                continue;
+            };
+            dependencies.assets.entry(path).or_default().entities.push(e);
+        }
+    }
+    drop(registry);
+    world.insert_resource(dependencies);
+}
+
+/// writes `data` to `path` through a temporary file, so a crash mid-write never leaves a half written save behind.
+pub fn write_atomic(path: impl AsRef<Path>, data: &[u8]) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(data)?;
+    // on disk before the rename, or a crash can leave the renamed save empty.
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp, path)?;
+
+    // the rename itself is only durable once the folder is synced. Folders can't be opened for that everywhere(windows).
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if let Ok(folder) = fs::File::open(parent) {
+        let _ = folder.sync_all();
+    }
+    Ok(())
+}
+
+/// serializes the world(with a [`SaveHeader`] in front), returning the save and the entities in it.
+fn serialize_world(
+    world: &mut World,
+    options: &SaveOptions,
+) -> Result<(Vec<u8>, Vec<Entity>), SaveError> {
+    #[allow(unused_mut)]
+    let mut scene = extract_save_scene(world, options)?;
+    #[cfg(feature = "render")]
+    offload_procedural_meshes(world, &mut scene, options);
+    let metadata = world.get_resource::<SaveMetadata>().cloned().unwrap_or_default();
+    let content = encode_scene(world, &scene, options.format)?;
+    let data = SaveHeader::new(&metadata, scene.entities.len()).seal(&content)?;
+    Ok((data, scene.entities.iter().map(|n| n.entity).collect()))
+}
+
+/// despawns everything that would be replaced by a load.
+pub fn unload_world(world: &mut World) {
+    let entities = world
+        .query_filtered::<Entity, Or<(With<Save>, With<Unload>)>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for e in entities {
+        // may have already been despawned along with a parent.
+        if let Ok(e) = world.get_entity_mut(e) {
+            e.despawn();
+        }
+    }
+}
+
+/// writes procedural meshes of entities in `scene` to sidecar mesh files, and points the [`Mesh3dFlag`]s in the scene at
+/// them. The world is left as it is.
+#[cfg(feature = "render")]
+pub fn offload_procedural_meshes(world: &World, scene: &mut DynamicScene, options: &SaveOptions) {
+    let ProceduralMeshes::Sidecar(sidecars) = &options.procedural_meshes else {
+        return;
+    };
+    if let Err(err) = fs::create_dir_all(&sidecars.folder) {
+        log::warn!(
+            "could not create sidecar mesh folder {:#}: {:#}. Embedding meshes instead",
+            sidecars.folder, err
+        );
+        return;
+    }
+
+    let mut written = HashSet::new();
+    for entity in &mut scene.entities {
+        // scene entities are still the world's entities right after extraction.
+        let Some(Mesh3dFlag::Pure(MeshWrapper::Procedural(mesh))) = world.get::<Mesh3dFlag>(entity.entity) else {
+            continue;
+        };
+        let Some(flag) = entity.components.iter_mut().find(|n| {
+            n.get_represented_type_info()
+                .is_some_and(|n| n.type_id() == TypeId::of::<Mesh3dFlag>())
+        }) else {
+            continue;
+        };
+        let bytes = mesh_to_bytes(mesh);
+        // content addressed so identical meshes share a file.
+        let file_name = format!("{:016x}.{}", content_hash(&bytes), MESH_FILE_EXTENSION);
+
+        if !written.contains(&file_name) {
+            let path = Path::new(&sidecars.folder).join(&file_name);
+            // written atomically, so a file that exists is whole(a crash mid-write can't leave a broken mesh behind).
+            if !path.exists() {
+                // left embedded. Later entities with the same mesh try writing it again.
+                if let Err(err) = write_atomic(&path, &bytes) {
+                    log::warn!("could not write sidecar mesh {:#?}: {:#}. Embedding it instead", path, err);
+                    continue;
+                }
+            }
+            written.insert(file_name.clone());
+        }
+        *flag = Box::new(Mesh3dFlag::Path(format!(
+            "{}/{}",
+            sidecars.asset_path.trim_end_matches('/'),
+            file_name
+        )));
+    }
+}
+
+/// whether any component that makes it into saves changed on `entities` since `since`.
+pub fn saved_components_changed(world: &World, entities: &[Entity], since: Tick) -> bool {
+    let this_run = world.read_change_tick();
+    entities.iter().any(|e| {
+        let entity = world.entity(*e);
+        saved_components(world, *e).into_iter().any(|component| {
+            entity
+                .get_change_ticks_by_id(component)
+                .is_some_and(|n| n.is_changed(since, this_run))
+        })
+    })
+}
+
+/// components of `entity` that are saved: let through by the [`SerializeFilter`], reflected, and not synonym targets.
+pub fn saved_components(world: &World, entity: Entity) -> HashSet<ComponentId> {
+    let filter = &world.resource::<SerializeFilter>().components;
+    let synonym_targets = world.get_resource::<SynonymTargets>();
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+
+    world
+        .entity(entity)
+        .archetype()
+        .components()
+        .filter(|component| {
+            let Some(type_id) = world
+                .components()
+                .get_info(*component)
+                .and_then(|n| n.type_id())
+            else {
+                return false;
+            };
+            filter.is_allowed_by_id(type_id)
+                && !synonym_targets.is_some_and(|n| n.contains_key(&type_id))
+                && type_registry
+                    .get(type_id)
+                    .is_some_and(|n| n.data::<ReflectComponent>().is_some())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::App;
+    use bevy_reflect::Reflect;
+    use bevy_scene::DynamicEntity;
+    use bevy_synonymize::{
+        persistent::{PersistentId, PersistentRef},
+        plugins::ResolvePersistentRefs,
+        traits::PersistentRefs,
+    };
+
+    use super::*;
+    use crate::{
+        autosave::{AutosaveSettings, AutosaveState},
+        resources::{SaveJob, SaveProfile, SaveQueue},
+        systems::autosave,
+    };
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<SerializeFilter>();
+        world
+    }
+
+    /// refers to an entity that may not be in the same save.
+    #[derive(Component, Reflect)]
+    #[reflect(Component)]
+    struct Target(#[entities] Entity);
+
+    #[test]
+    fn counts_only_saved_entities() {
+        let mut world = world();
+        world.resource::<AppTypeRegistry>().write().register::<Target>();
+        let scene = DynamicScene {
+            resources: Vec::new(),
+            entities: vec![DynamicEntity {
+                entity: Entity::from_raw(3),
+                components: vec![Box::new(Target(Entity::from_raw(7)))],
+            }],
+        };
+        let save = encode_scene(&world, &scene, SaveFormat::Ron).unwrap();
+        let folder = std::env::temp_dir().join(format!("saved_entities_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("save.ron").to_string_lossy().into_owned();
+        fs::write(&path, &save).unwrap();
+
+        let options = LoadOptions {
+            unload: false,
+            ..Default::default()
+        };
+        // the entity `Target` points at isn't in the save, and gets a placeholder while loading.
+        let (entity_count, _) = load_world_from_bytes(&mut world, &save, &options).unwrap();
+        assert_eq!(entity_count, 1);
+        let loaded = load_world(&mut world, &SavePath::File(path), &options).unwrap();
+        assert_eq!(loaded.entity_count, 1);
+        let base = world.resource::<DeltaBase>();
+        assert_eq!(base.ids.values().copied().collect::<Vec<_>>(), [Entity::from_raw(3)]);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn detects_format() {
+        let mut binary = SaveFormat::BINARY_MAGIC.to_vec();
+        binary.extend([1, 2, 3]);
+        assert_eq!(SaveFormat::detect(Some("ron"), &binary), SaveFormat::Binary);
+        assert_eq!(SaveFormat::detect(Some("bin"), b""), SaveFormat::Binary);
+        assert_eq!(SaveFormat::detect(Some("scn"), b"("), SaveFormat::Ron);
+        assert_eq!(SaveFormat::detect(None, b"  {\"entities\": {}}"), SaveFormat::Json);
+        assert_eq!(SaveFormat::detect(None, b"(entities: {})"), SaveFormat::Ron);
+    }
+
+    #[test]
+    fn truncated_binary_save_is_corrupted() {
+        let world = world();
+        for bytes in [&b""[..], b"BS", b"BSX"] {
+            let result = decode_scene(&world, bytes, SaveFormat::Binary, false);
+            assert!(matches!(result, Err(LoadError::Corrupted(_))));
+        }
+    }
+
+    #[test]
+    fn autosave_keeps_delta_base() {
+        let mut world = world();
+        world.init_resource::<SerializeFilter>();
+        world.init_resource::<SaveQueue>();
+        let folder = std::env::temp_dir().join(format!("delta_base_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let base = folder.join("base.ron").to_string_lossy().into_owned();
+        world.insert_resource(AutosaveSettings {
+            folder: folder.to_string_lossy().into_owned(),
+            ..Default::default()
+        });
+        world.init_resource::<AutosaveState>();
+
+        save_world(&mut world, &SavePath::File(base.clone()), &SaveOptions::default()).unwrap();
+        autosave(&mut world);
+        let Some(SaveJob::Save { path, options }) = world.resource_mut::<SaveQueue>().pop_front() else {
+            panic!("no autosave was queued");
+        };
+        save_world(&mut world, &path, &options).unwrap();
+
+        assert_eq!(world.resource::<DeltaBase>().path, base);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn truncated_ron_save_fails() {
+        let world = world();
+        let result = decode_scene(&world, b"(\n  resources: {},\n  entit", SaveFormat::Ron, false);
+        assert!(matches!(result, Err(LoadError::Deserialize(_))));
+    }
+
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Player;
+
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Secret;
+
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Hidden;
+
+    #[test]
+    fn profile_filters_entities_and_components() {
+        let mut world = world();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<Player>();
+            registry.register::<Secret>();
+            registry.register::<Hidden>();
+            registry.register::<ChildOf>();
+        }
+        world.insert_resource(SaveProfiles::default().with(
+            "players",
+            SaveProfile::default().with::<Player>().not_under::<Hidden>().deny::<Secret>(),
+        ));
+        let player = world.spawn((Save, Player, Secret)).id();
+        world.spawn(Save);
+        let hidden = world.spawn((Save, Hidden)).id();
+        world.spawn((Save, Player, ChildOf(hidden)));
+
+        let options = SaveOptions {
+            profile: Some("players".to_owned()),
+            ..Default::default()
+        };
+        let scene = extract_save_scene(&mut world, &options).unwrap();
+        assert_eq!(scene.entities.len(), 1);
+        assert_eq!(scene.entities[0].entity, player);
+        let components = scene.entities[0]
+            .components
+            .iter()
+            .map(|n| n.reflect_type_path().to_owned())
+            .collect::<Vec<_>>();
+        assert!(components.iter().any(|n| n.ends_with("Player")));
+        assert!(!components.iter().any(|n| n.ends_with("Secret")));
+
+        // everything without a profile.
+        assert_eq!(extract_save_scene(&mut world, &SaveOptions::default()).unwrap().entities.len(), 4);
+        let unknown = SaveOptions {
+            profile: Some("unknown".to_owned()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            extract_save_scene(&mut world, &unknown),
+            Err(SaveError::UnknownProfile(_))
+        ));
+    }
+
+    /// follows an entity that may be in another save.
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Follows(#[entities] PersistentRef);
+
+    impl PersistentRefs for Follows {
+        fn persistent_refs(&mut self) -> Vec<&mut PersistentRef> {
+            vec![&mut self.0]
+        }
+    }
+
+    #[test]
+    fn resolves_refs_to_other_saves_after_load() {
+        let mut app = App::new();
+        app.add_plugins(ResolvePersistentRefs::<Follows>::default());
+        let world = app.world_mut();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<SerializeFilter>();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<Follows>();
+            registry.register::<Player>();
+        }
+        world.insert_resource(SaveProfiles::default().with("followers", SaveProfile::default().with::<Follows>()));
+        let id = PersistentId::new();
+        let target = world.spawn((Save, Player, id)).id();
+        let follower = world.spawn((Save, Follows(PersistentRef::new(target)))).id();
+        // fills in the id.
+        app.update();
+
+        let folder = std::env::temp_dir().join(format!("resolves_refs_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let path = SavePath::File(folder.join("followers.ron").to_string_lossy().into_owned());
+        let options = SaveOptions {
+            profile: Some("followers".to_owned()),
+            ..Default::default()
+        };
+        let world = app.world_mut();
+        assert_eq!(save_world(world, &path, &options).unwrap().entity_count, 1);
+
+        // the target comes back from somewhere else(e.g: its level), as a new entity.
+        world.despawn(follower);
+        world.despawn(target);
+        let target = world.spawn((Save, Player, id)).id();
+        load_world(world, &path, &LoadOptions { unload: false, ..Default::default() }).unwrap();
+        app.update();
+
+        let world = app.world_mut();
+        let follows = world.query::<&Follows>().single(world).unwrap();
+        assert_eq!(follows.0.entity, target);
+        assert_eq!(follows.0.id, Some(id));
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+}
@@ -3,40 +3,55 @@
 // use crate::prelude::material::Material3dFlag;
 // use crate::prelude::mesh::Mesh3dFlag;
 // use crate::traits::*;
+#[cfg(feature = "render")]
 use bevy_core_pipeline::core_3d::{Camera3dDepthTextureUsage, ScreenSpaceTransmissionQuality};
+#[cfg(feature = "render")]
 use bevy_render::camera::{CameraMainTextureUsages, CameraRenderGraph};
-use log::warn;
-use moonshine_save::file_from_resource;
 use moonshine_save::load::LoadPlugin;
-use moonshine_save::load::load;
-use moonshine_save::save::SaveInput;
+use moonshine_save::save::Save;
 use moonshine_save::save::SavePlugin;
-use moonshine_save::save::save_with;
-use std::any::type_name;
+use bevy_synonymize::persistent::PersistentId;
+use bevy_synonymize::resources::PersistentIds;
 use std::ops::Range;
-use std::{any::TypeId, marker::PhantomData};
+use std::time::Duration;
 
 use bevy_app::prelude::*;
+use bevy_asset::io::AssetSource;
+use bevy_asset::io::memory::{Dir, MemoryAssetReader};
 use bevy_asset::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_math::prelude::*;
+#[cfg(feature = "render")]
 use bevy_pbr::prelude::*;
 use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+#[cfg(feature = "render")]
 use bevy_render::prelude::*;
 
-use crate::resources::ComponentsOnSave;
-use crate::resources::LoadRequest;
-use crate::resources::RefreshCounter;
-use crate::resources::SaveRequest;
-use crate::resources::SerializeFilter;
-use crate::resources::ShowSerializable;
-use crate::resources::ShowUnserializable;
-use crate::resources::SynonymAssetDeserializers;
-use crate::resources::SynonymAssetSerializers;
-use crate::resources::SynonymCompDeserializers;
-use crate::resources::SynonymCompSerializers;
-use crate::resources::TypeRegistryOnSave;
-use crate::systems::update_last_saved_typedata;
+#[cfg(feature = "render")]
+use crate::mesh_file::MeshFileLoader;
+use crate::{
+    autosave::{AutosaveSettings, AutosaveState},
+    bundle::{BUNDLE_SOURCE, SaveBundles},
+    chunks::{ChunkSettings, LoadedChunks},
+    events::{
+        ChunkLoaded, ChunkSaved, LoadCompleted, LoadFailed, MissingAssets, PrefabSpawned, SaveCompleted, SaveFailed,
+        SaveReloaded, TriggerAutosave,
+    },
+    hot_reload::{SAVE_FILE_SOURCE, SaveFileAsset, SaveFileLoader, SaveHotReload},
+    resources::{
+        ComponentsOnSave, LoadOptions, LoadReport, LoadRequest, RefreshCounter, SaveDependencies, SaveOptions,
+        SaveProfiles, SaveQueue, SaveRequest, SerializabilityReport, SerializeFilter, ShowSerializable,
+        ShowUnserializable, SynonymAssetDeserializers, SynonymAssetSerializers, SynonymCompDeserializers,
+        SynonymCompSerializers, TypeRegistryOnSave,
+    },
+    slots::SaveMetadata,
+    systems::{
+        autosave, autosave_due, process_save_queue, queue_requests, reload_modified_save, reset_undo_on_load,
+        stream_chunks_around_focus, tick_autosave, track_save_dependencies, update_last_saved_typedata,
+        update_serializability_report, watch_loaded_save,
+    },
+    undo::{UndoHistory, UndoSettings, record_changes, snapshot_despawned},
+};
 
 
 
@@ -98,19 +113,23 @@ pub struct SerializationPlugin;
 
 impl Plugin for SerializationPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<[f32; 3]>()
-            .register_type::<AlphaMode>()
+        #[cfg(feature = "render")]
+        app.register_type::<AlphaMode>()
             .register_type::<ParallaxMappingMethod>()
             .register_type::<Camera3dDepthTextureUsage>()
             .register_type::<InheritedVisibility>()
             .register_type::<ScreenSpaceTransmissionQuality>()
+            .register_type::<CameraRenderGraph>()
+            .register_type::<CameraMainTextureUsages>();
+
+        app.register_type::<[f32; 3]>()
             .register_type::<[[f32; 3]; 3]>()
             .register_type::<[Vec3; 3]>()
-            .register_type::<CameraRenderGraph>()
-            .register_type::<CameraMainTextureUsages>()
             .register_type::<TypeRegistryOnSave>()
             .register_type::<LoadRequest>()
             .register_type::<SaveRequest>()
+            .register_type::<SaveOptions>()
+            .register_type::<LoadOptions>()
             .register_type::<ComponentsOnSave>()
             .register_type::<ShowSerializable>()
             .register_type::<ShowUnserializable>()
@@ -122,32 +141,185 @@ impl Plugin for SerializationPlugin {
             .insert_resource(ShowUnserializable::default())
             .insert_resource(ComponentsOnSave::default())
             .insert_resource(TypeRegistryOnSave::default())
-            .insert_resource(RefreshCounter::default());
+            .insert_resource(RefreshCounter::default())
+            .init_resource::<SaveQueue>()
+            .init_resource::<SaveMetadata>()
+            .init_resource::<SaveProfiles>()
+            .register_type::<PersistentId>()
+            .init_resource::<PersistentIds>()
+            .register_required_components::<Save, PersistentId>()
+            .init_resource::<SerializabilityReport>()
+            .init_resource::<LoadReport>()
+            .add_event::<SaveCompleted>()
+            .add_event::<SaveFailed>()
+            .add_event::<LoadCompleted>()
+            .add_event::<LoadFailed>()
+            .add_event::<PrefabSpawned>()
+            .add_event::<SaveReloaded>()
+            .init_resource::<SaveDependencies>()
+            .add_event::<MissingAssets>();
         app.add_plugins((SavePlugin, LoadPlugin))
             .add_systems(
                 PreUpdate,
-                update_last_saved_typedata.run_if(resource_added::<SaveRequest>),
-            )
-            .add_systems(
-                PreUpdate,
-                update_last_saved_typedata.run_if(resource_added::<LoadRequest>),
-            )
-            .add_systems(
-                PreUpdate,
-                update_last_saved_typedata.run_if(resource_changed::<RefreshCounter>),
-            )
-            .add_systems(
-                PreUpdate,
-                save_with(save_filter).into(file_from_resource::<SaveRequest>()),
+                (update_last_saved_typedata, update_serializability_report)
+                    .run_if(resource_changed::<RefreshCounter>),
             )
+            .add_systems(PreUpdate, (queue_requests, process_save_queue).chain())
+            .add_systems(Update, track_save_dependencies)
             .init_resource::<SynonymAssetSerializers>()
             .init_resource::<SynonymAssetDeserializers>()
             .init_resource::<SynonymCompSerializers>()
-            .init_resource::<SynonymCompDeserializers>()
-            .add_systems(PreUpdate, load(file_from_resource::<LoadRequest>()));
+            .init_resource::<SynonymCompDeserializers>();
+    }
+
+    #[cfg(feature = "render")]
+    fn finish(&self, app: &mut App) {
+        // the asset server only exists once `AssetPlugin` is built.
+        app.init_asset_loader::<MeshFileLoader>();
     }
 }
-/// save filter for this library.
-fn save_filter(f: Res<SerializeFilter>) -> SaveInput {
-    f.0.clone()
+
+/// periodically(or on [`TriggerAutosave`]) saves the world into a rotating set of slots. Requires [`SerializationPlugin`].
+///
+/// Autosaves are skipped when nothing saved has changed since the last one.
+#[derive(Default)]
+pub struct AutosavePlugin(pub AutosaveSettings);
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        // continue after the newest slot so it isn't the first to be overwritten.
+        let next_slot = self
+            .0
+            .newest_slot()
+            .map(|n| (n + 1) % self.0.slots.max(1))
+            .unwrap_or(0);
+
+        app.insert_resource(self.0.clone())
+            .insert_resource(AutosaveState {
+                next_slot,
+                ..Default::default()
+            })
+            .add_event::<TriggerAutosave>()
+            .add_systems(
+                PreUpdate,
+                (tick_autosave, autosave.run_if(autosave_due))
+                    .chain()
+                    .before(process_save_queue),
+            );
+    }
+}
+
+/// records an undo history of edits to synonyms on [`Save`] entities, and of [`Save`] entities being spawned/despawned.
+/// Undo/redo with [`UndoCommands`]. Requires [`SerializationPlugin`].
+///
+/// [`Save`]: moonshine_save::save::Save
+/// [`UndoCommands`]: crate::traits::UndoCommands
+#[derive(Default)]
+pub struct UndoPlugin(pub UndoSettings);
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone())
+            .init_resource::<UndoHistory>()
+            .add_observer(snapshot_despawned)
+            .add_systems(Last, (reset_undo_on_load, record_changes).chain());
+    }
+}
+
+/// registers the `bundle://` asset source that assets of loaded save bundles are served from. See [`crate::bundle`].
+///
+/// Must be added before `AssetPlugin`(`DefaultPlugins`).
+pub struct SaveBundlePlugin {
+    /// folder assets are read from when exporting bundles. Same as `AssetPlugin::file_path`.
+    pub asset_folder: String,
+}
+
+impl Default for SaveBundlePlugin {
+    fn default() -> Self {
+        Self {
+            asset_folder: "assets".to_owned(),
+        }
+    }
+}
+
+impl Plugin for SaveBundlePlugin {
+    fn build(&self, app: &mut App) {
+        let assets = Dir::default();
+        let root = assets.clone();
+        app.register_asset_source(
+            BUNDLE_SOURCE,
+            AssetSource::build().with_reader(move || Box::new(MemoryAssetReader { root: root.clone() })),
+        )
+        .insert_resource(SaveBundles {
+            asset_folder: self.asset_folder.clone(),
+            assets,
+        });
+    }
+}
+
+/// reloads the last save loaded from a file under `folder` when it changes on disk, updating its entities in place.
+/// See [`crate::hot_reload`]. Requires [`SerializationPlugin`].
+///
+/// Meant for development: needs bevy's `file_watcher` feature. Must be added before `AssetPlugin`(`DefaultPlugins`).
+pub struct SaveHotReloadPlugin {
+    /// folder(relative to the working directory) to watch saves in.
+    pub folder: String,
+}
+
+impl Default for SaveHotReloadPlugin {
+    fn default() -> Self {
+        Self {
+            folder: "saves".to_owned(),
+        }
+    }
+}
+
+impl Plugin for SaveHotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        // saves are relative to the working directory, asset sources to the executable/manifest.
+        let root = std::env::current_dir()
+            .map(|n| n.join(&self.folder))
+            .unwrap_or_else(|_| self.folder.clone().into())
+            .to_string_lossy()
+            .into_owned();
+        app.register_asset_source(
+            SAVE_FILE_SOURCE,
+            AssetSource::build()
+                .with_reader(AssetSource::get_default_reader(root.clone()))
+                .with_watcher(AssetSource::get_default_watcher(root, Duration::from_millis(300))),
+        )
+        .insert_resource(SaveHotReload {
+            folder: self.folder.clone(),
+            ..Default::default()
+        })
+        .add_systems(
+            PreUpdate,
+            (watch_loaded_save, reload_modified_save)
+                .chain()
+                .before(process_save_queue),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        // the asset server only exists once `AssetPlugin` is built.
+        app.init_asset::<SaveFileAsset>()
+            .init_asset_loader::<SaveFileLoader>();
+    }
+}
+
+/// saves/loads [`Save`] entities in chunks of a world-space grid, loading chunks around [`ChunkFocus`] entities and
+/// saving/unloading the rest. See [`crate::chunks`]. Requires [`SerializationPlugin`].
+///
+/// [`ChunkFocus`]: crate::chunks::ChunkFocus
+#[derive(Default)]
+pub struct ChunkedSavePlugin(pub ChunkSettings);
+
+impl Plugin for ChunkedSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone())
+            .init_resource::<LoadedChunks>()
+            .add_event::<ChunkLoaded>()
+            .add_event::<ChunkSaved>()
+            .add_systems(PreUpdate, stream_chunks_around_focus.before(process_save_queue));
+    }
 }
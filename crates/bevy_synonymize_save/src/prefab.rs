@@ -0,0 +1,193 @@
+//! saving an entity and its descendants as a prefab, and spawning copies of it.
+
+use std::any::TypeId;
+
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_reflect::PartialReflect;
+use bevy_scene::SceneFilter;
+use bevy_synonymize::persistent::PersistentId;
+use bevy_transform::prelude::*;
+
+#[cfg(feature = "render")]
+use crate::pipeline::offload_procedural_meshes;
+use crate::{
+    events::{LoadError, SaveCompleted, SaveError},
+    pipeline::{decode_scene, encode_scene, extract_scene, read_save, save_input, write_atomic},
+    resources::{SaveOptions, SavePath},
+    slots::{SaveHeader, SaveMetadata, split_header, verify_save},
+};
+
+/// saves `root` and its descendants to `path` as a prefab.
+///
+/// Entities/components go through the save profile in `options` like world saves. Resources and [`PersistentId`]s are left out.
+/// Prefabs have a [`SaveHeader`] like world saves, which also records which entity is the root.
+pub fn save_prefab(
+    world: &mut World,
+    root: Entity,
+    path: &str,
+    options: &SaveOptions,
+) -> Result<SaveCompleted, SaveError> {
+    let entities = descendants(world, root);
+    let mut input = save_input(world, &entities, options)?;
+    input.resources = SceneFilter::deny_all();
+    let mut scene = extract_scene(world, entities, input);
+    #[cfg(feature = "render")]
+    offload_procedural_meshes(world, &mut scene, options);
+
+    // the root's parent is not part of the prefab. Copies are spawned without one.
+    let Some(prefab_root) = scene.entities.iter_mut().find(|n| n.entity == root) else {
+        return Err(SaveError::PrefabRootFiltered(root));
+    };
+    prefab_root
+        .components
+        .retain(|n| !is_type::<ChildOf>(n.as_ref()));
+    // every copy needs ids of its own.
+    for entity in &mut scene.entities {
+        entity
+            .components
+            .retain(|n| !is_type::<PersistentId>(n.as_ref()));
+    }
+
+    let metadata = world.get_resource::<SaveMetadata>().cloned().unwrap_or_default();
+    let content = encode_scene(world, &scene, options.format)?;
+    let header = SaveHeader {
+        prefab_root: Some(root),
+        ..SaveHeader::new(&metadata, scene.entities.len())
+    };
+    let data = header.seal(&content)?;
+    write_atomic(path, &data)?;
+
+    Ok(SaveCompleted {
+        path: SavePath::File(path.to_owned()),
+        entity_count: scene.entities.len(),
+        bytes: data.len(),
+    })
+}
+
+/// spawns a copy of the prefab at `path`, with its root at `transform`. Returns the root.
+///
+/// Every copy gets fresh entities, and references between entities in the prefab are remapped to the copy.
+/// If `root` is given, the prefab's root is spawned into it.
+pub fn spawn_prefab(
+    world: &mut World,
+    path: &str,
+    transform: Transform,
+    root: Option<Entity>,
+) -> Result<Entity, LoadError> {
+    let (data, format) = read_save(world, &SavePath::File(path.to_owned()))?;
+    let content = verify_save(&data)?;
+    let (scene, _) = decode_scene(world, content, format, false)?;
+
+    // prefabs written before the header recorded the root have theirs as the only entity without a parent.
+    let prefab_root = split_header(&data)
+        .0
+        .and_then(|n| n.prefab_root)
+        .or_else(|| {
+            scene
+                .entities
+                .iter()
+                .find(|n| !n.components.iter().any(|n| is_type::<ChildOf>(n.as_ref())))
+                .map(|n| n.entity)
+        })
+        .filter(|root| scene.entities.iter().any(|n| n.entity == *root))
+        .ok_or(LoadError::NoPrefabRoot)?;
+
+    let mut entity_map = EntityHashMap::default();
+    if let Some(root) = root {
+        entity_map.insert(prefab_root, root);
+    }
+    scene.write_to_world(world, &mut entity_map)?;
+
+    let root = entity_map[&prefab_root];
+    world.entity_mut(root).insert(transform);
+    Ok(root)
+}
+
+/// `root`, followed by all of its descendants.
+fn descendants(world: &World, root: Entity) -> Vec<Entity> {
+    let mut entities = vec![root];
+    let mut i = 0;
+    while let Some(e) = entities.get(i).copied() {
+        if let Some(children) = world.get::<Children>(e) {
+            entities.extend_from_slice(children);
+        }
+        i += 1;
+    }
+    entities
+}
+
+fn is_type<T: 'static>(value: &dyn PartialReflect) -> bool {
+    value
+        .get_represented_type_info()
+        .is_some_and(|n| n.type_id() == TypeId::of::<T>())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fs};
+
+    use bevy_reflect::Reflect;
+
+    use super::*;
+    use crate::resources::SerializeFilter;
+
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Label(u32);
+
+    /// `entity` and its descendants.
+    fn hierarchy(world: &World, entity: Entity) -> HashSet<Entity> {
+        descendants(world, entity).into_iter().collect()
+    }
+
+    #[test]
+    fn spawns_copies() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world.init_resource::<SerializeFilter>();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<Label>();
+            registry.register::<ChildOf>();
+            registry.register::<Children>();
+            registry.register::<PersistentId>();
+            registry.register::<Transform>();
+        }
+        let outside = world.spawn(Label(0)).id();
+        let root = world.spawn((Label(1), PersistentId::new(), ChildOf(outside))).id();
+        let child = world.spawn((Label(2), PersistentId::new(), ChildOf(root))).id();
+        world.spawn((Label(3), ChildOf(child)));
+
+        let folder = std::env::temp_dir().join(format!("spawns_copies_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("prefab.ron").to_string_lossy().into_owned();
+        let saved = save_prefab(&mut world, root, &path, &SaveOptions::default()).unwrap();
+        assert_eq!(saved.entity_count, 3);
+
+        let first = spawn_prefab(&mut world, &path, Transform::from_xyz(1.0, 0.0, 0.0), None).unwrap();
+        let second = spawn_prefab(&mut world, &path, Transform::default(), None).unwrap();
+        for copy in [first, second] {
+            assert_ne!(copy, root);
+            assert_eq!(world.get::<Label>(copy), Some(&Label(1)));
+            assert!(world.get::<ChildOf>(copy).is_none());
+            assert!(world.get::<PersistentId>(copy).is_none());
+
+            // the hierarchy is rebuilt under the new root.
+            let children = world.get::<Children>(copy).unwrap();
+            assert_eq!(children.len(), 1);
+            let child = children[0];
+            assert_eq!(world.get::<Label>(child), Some(&Label(2)));
+            assert_eq!(world.get::<ChildOf>(child).map(|n| n.parent()), Some(copy));
+            let grandchild = world.get::<Children>(child).unwrap()[0];
+            assert_eq!(world.get::<Label>(grandchild), Some(&Label(3)));
+            assert_eq!(hierarchy(&world, copy).len(), 3);
+        }
+        assert_eq!(world.get::<Transform>(first), Some(&Transform::from_xyz(1.0, 0.0, 0.0)));
+        assert!(hierarchy(&world, first).is_disjoint(&hierarchy(&world, second)));
+        assert!(hierarchy(&world, first).is_disjoint(&hierarchy(&world, root)));
+        // the original is left alone.
+        assert_eq!(world.get::<ChildOf>(root).map(|n| n.parent()), Some(outside));
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
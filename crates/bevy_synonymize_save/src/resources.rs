@@ -1,10 +1,24 @@
-use std::{any::TypeId, collections::HashMap, path::Path};
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Display, Formatter},
+};
 
+use bevy_asset::{Handle, LoadState, LoadedUntypedAsset};
 use bevy_derive::{Deref, DerefMut};
-use bevy_ecs::{component::ComponentId, prelude::*, system::SystemId};
+use bevy_ecs::{
+    component::{ComponentId, Tick},
+    entity::EntityHashMap,
+    prelude::*,
+    system::SystemId,
+};
 use bevy_reflect::Reflect;
-use bevy_render::{camera::{CameraMainTextureUsages, CameraRenderGraph, Exposure}, mesh::Mesh3d};
-use moonshine_save::{prelude::GetFilePath, save::{EntityFilter, SaveInput}};
+use bevy_scene::SceneFilter;
+#[cfg(feature = "render")]
+use bevy_render::camera::{CameraMainTextureUsages, CameraRenderGraph, Exposure};
+use moonshine_save::save::{EntityFilter, SaveInput};
+
+use crate::{bundle::BundleKind, slots::split_header};
 
 
 
@@ -19,40 +33,424 @@ impl Default for SerializeFilter {
             let mut new_filter = SaveInput::default();
 
             new_filter.entities = EntityFilter::Any;
-            new_filter.components = new_filter
-                .components
-                .clone()
-                .deny::<CameraMainTextureUsages>()
-                .deny::<CameraRenderGraph>()
-                .deny::<Exposure>()
-                .deny::<Mesh3d>();
+            #[cfg(feature = "render")]
+            {
+                new_filter.components = new_filter
+                    .components
+                    .clone()
+                    .deny::<CameraMainTextureUsages>()
+                    .deny::<CameraRenderGraph>()
+                    .deny::<Exposure>();
+            }
             new_filter
         })
     }
 }
 
+/// named [`SaveProfile`]s saves can pick between with [`SaveOptions::profile`].
+#[derive(Resource, Clone, Default, Deref, DerefMut)]
+pub struct SaveProfiles(pub HashMap<String, SaveProfile>);
+
+impl SaveProfiles {
+    pub fn with(mut self, name: impl Into<String>, profile: SaveProfile) -> Self {
+        self.0.insert(name.into(), profile);
+        self
+    }
+}
+
+/// what a save includes, in place of the [`SerializeFilter`]. e.g: a full editor save, or a level export without the player.
+///
+/// Only [`Save`](moonshine_save::save::Save) entities are ever saved. Synonym targets are always skipped.
+#[derive(Clone)]
+pub struct SaveProfile {
+    pub components: SceneFilter,
+    /// resources to save. None by default.
+    pub resources: SceneFilter,
+    /// entities are only saved if they match every predicate.
+    pub entities: Vec<EntityPredicate>,
+}
+
+impl Default for SaveProfile {
+    fn default() -> Self {
+        Self {
+            components: SerializeFilter::default().0.components,
+            resources: SceneFilter::deny_all(),
+            entities: Vec::new(),
+        }
+    }
+}
+
+impl SaveProfile {
+    pub fn allow<T: Component>(mut self) -> Self {
+        self.components = self.components.allow::<T>();
+        self
+    }
+
+    pub fn deny<T: Component>(mut self) -> Self {
+        self.components = self.components.deny::<T>();
+        self
+    }
+
+    pub fn allow_resource<T: Resource>(mut self) -> Self {
+        self.resources = self.resources.allow::<T>();
+        self
+    }
+
+    /// only save entities with `T`.
+    pub fn with<T: Component>(mut self) -> Self {
+        self.entities.push(EntityPredicate::With(TypeId::of::<T>()));
+        self
+    }
+
+    /// only save entities without `T`.
+    pub fn without<T: Component>(mut self) -> Self {
+        self.entities.push(EntityPredicate::Without(TypeId::of::<T>()));
+        self
+    }
+
+    /// only save hierarchies with `T` on their root(or any other ancestor).
+    pub fn under<T: Component>(mut self) -> Self {
+        self.entities.push(EntityPredicate::Under(TypeId::of::<T>()));
+        self
+    }
+
+    /// skip hierarchies with `T` on their root(or any other ancestor).
+    pub fn not_under<T: Component>(mut self) -> Self {
+        self.entities.push(EntityPredicate::NotUnder(TypeId::of::<T>()));
+        self
+    }
+}
+
+/// condition on which entities a [`SaveProfile`] saves. Components are by [`TypeId`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityPredicate {
+    With(TypeId),
+    Without(TypeId),
+    /// the entity, or one of its ancestors, has the component.
+    Under(TypeId),
+    NotUnder(TypeId),
+}
+
+impl EntityPredicate {
+    pub fn matches(&self, world: &World, entity: Entity) -> bool {
+        let has = |e: Entity, marker: TypeId| {
+            world
+                .get_entity(e)
+                .is_ok_and(|n| n.contains_type_id(marker))
+        };
+        let under = |marker: TypeId| {
+            let mut current = Some(entity);
+            while let Some(e) = current {
+                if has(e, marker) {
+                    return true;
+                }
+                current = world.get::<ChildOf>(e).map(|n| n.parent());
+            }
+            false
+        };
+        match *self {
+            Self::With(marker) => has(entity, marker),
+            Self::Without(marker) => !has(entity, marker),
+            Self::Under(marker) => under(marker),
+            Self::NotUnder(marker) => !under(marker),
+        }
+    }
+}
 
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct SaveRequest {
     pub path: String,
+    pub options: SaveOptions,
+}
+
+impl SaveRequest {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            options: SaveOptions::default(),
+        }
+    }
+}
+
+/// settings for how a save is written.
+#[derive(Reflect, Clone, Debug)]
+pub struct SaveOptions {
+    pub format: SaveFormat,
+    pub procedural_meshes: ProceduralMeshes,
+    /// name of the [`SaveProfile`] in [`SaveProfiles`] to save with. Saves through the [`SerializeFilter`] if `None`.
+    pub profile: Option<String>,
+    /// make a save to a file the [`DeltaBase`] later deltas are made against. Autosaves turn this off, as their slots
+    /// get overwritten.
+    pub delta_base: bool,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            format: SaveFormat::default(),
+            procedural_meshes: ProceduralMeshes::default(),
+            profile: None,
+            delta_base: true,
+        }
+    }
+}
+
+/// encoding of a save. Detected automatically on load.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaveFormat {
+    /// bevy scene format. Human readable.
+    #[default]
+    Ron,
+    /// compact, non-human readable format for autosaves/snapshots sent over the network.
+    ///
+    /// Starts with [`SaveFormat::BINARY_MAGIC`].
+    Binary,
+    /// pretty printed json. For web tooling/diffing.
+    Json,
 }
 
-impl GetFilePath for SaveRequest {
-    fn path(&self) -> &Path {
-        self.path.as_ref()
+impl SaveFormat {
+    /// magic bytes binary saves start with.
+    pub const BINARY_MAGIC: [u8; 4] = *b"BSXB";
+
+    /// file extension saves of this format are expected to have.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Ron => "ron",
+            Self::Binary => "bin",
+            Self::Json => "json",
+        }
+    }
+
+    /// guesses the format of a save from its magic bytes, then its file extension, then its first character.
+    ///
+    /// `bytes` may start with a [`SaveHeader`](crate::slots::SaveHeader).
+    pub fn detect(extension: Option<&str>, bytes: &[u8]) -> Self {
+        let (_, bytes) = split_header(bytes);
+        if bytes.starts_with(&Self::BINARY_MAGIC) {
+            return Self::Binary;
+        }
+        match extension {
+            Some("ron") | Some("scn") => Self::Ron,
+            Some("bin") => Self::Binary,
+            Some("json") => Self::Json,
+            _ => match bytes.iter().find(|n| !n.is_ascii_whitespace()) {
+                Some(b'{') => Self::Json,
+                _ => Self::Ron,
+            },
+        }
     }
 }
 
+/// how procedural(non-file) meshes on saved entities are written. (Requires the `render` feature)
+#[derive(Reflect, Clone, Debug, Default)]
+pub enum ProceduralMeshes {
+    /// keep procedural meshes inside of the save.
+    #[default]
+    Embed,
+    /// write each unique procedural mesh to its own sidecar mesh file, and point its [`Mesh3dFlag`] at it.
+    ///
+    /// [`Mesh3dFlag`]: bevy_synonymize::prelude::mesh::Mesh3dFlag
+    Sidecar(MeshSidecars),
+}
+
+/// where sidecar mesh files are written to, and how the asset server finds them again.
+#[derive(Reflect, Clone, Debug)]
+pub struct MeshSidecars {
+    /// folder(relative to the working directory, like [`SaveRequest::path`]) sidecar meshes are written to.
+    pub folder: String,
+    /// asset path that [`Self::folder`] is loaded through by the asset server. E.G: `root://saves/meshes`
+    pub asset_path: String,
+}
+
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct LoadRequest {
     pub path: String,
+    pub options: LoadOptions,
+}
+
+impl LoadRequest {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            options: LoadOptions::default(),
+        }
+    }
+}
+
+/// settings for how a save is loaded.
+#[derive(Reflect, Clone, Debug)]
+pub struct LoadOptions {
+    /// despawn entities marked with [`Save`]/[`Unload`] before spawning the save in.
+    ///
+    /// [`Save`]: moonshine_save::save::Save
+    /// [`Unload`]: moonshine_save::load::Unload
+    pub unload: bool,
+    /// skip components/resources that are unregistered instead of failing the load. Skipped types are listed in the [`LoadReport`].
+    ///
+    /// Only works for ron/json saves. Binary saves can not skip over types they don't know.
+    pub lenient: bool,
+    /// check the save against the length/hash in its header first, failing with [`LoadError::Corrupted`] if it was
+    /// truncated or damaged. Turn off to load saves edited by hand.
+    ///
+    /// [`LoadError::Corrupted`]: crate::events::LoadError::Corrupted
+    pub verify: bool,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            unload: true,
+            lenient: false,
+            verify: true,
+        }
+    }
+}
+
+/// what was skipped during the last load.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct LoadReport {
+    /// type paths of skipped components, and the number of entities they were skipped on.
+    pub skipped_components: HashMap<String, usize>,
+    /// type paths of skipped resources.
+    pub skipped_resources: Vec<String>,
+}
+
+impl LoadReport {
+    /// whether nothing was skipped.
+    pub fn is_complete(&self) -> bool {
+        self.skipped_components.is_empty() && self.skipped_resources.is_empty()
+    }
+}
+
+/// asset paths referenced by asset synonyms in the last loaded save, and how loading them went.
+///
+/// Once every asset finished loading, failed ones are sent as a [`MissingAssets`] event.
+///
+/// [`MissingAssets`]: crate::events::MissingAssets
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SaveDependencies {
+    /// the save these are the dependencies of.
+    pub save: Option<SavePath>,
+    pub assets: HashMap<String, AssetDependency>,
+    /// whether [`MissingAssets`](crate::events::MissingAssets) has been sent for these.
+    pub reported: bool,
+}
+
+impl SaveDependencies {
+    /// whether every asset has loaded or failed to.
+    pub fn is_finished(&self) -> bool {
+        self.assets.values().all(|n| {
+            matches!(n.state, Some(LoadState::Loaded) | Some(LoadState::Failed(_)))
+        })
+    }
+}
+
+/// an asset referenced by a loaded save.
+#[derive(Clone, Debug, Default)]
+pub struct AssetDependency {
+    /// entities with synonyms that refer to the asset.
+    pub entities: Vec<Entity>,
+    /// `None` until the asset server has started loading the asset.
+    pub state: Option<LoadState>,
+    /// keeps the asset loading if it was only loaded to see whether it can be.
+    pub(crate) handle: Option<Handle<LoadedUntypedAsset>>,
+}
+
+/// where a save is written to/loaded from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SavePath {
+    File(String),
+    /// the [`WorldSnapshot`] resource.
+    Snapshot,
+}
+
+impl Display for SavePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path),
+            Self::Snapshot => write!(f, "world snapshot"),
+        }
+    }
+}
+
+impl From<String> for SavePath {
+    fn from(value: String) -> Self {
+        Self::File(value)
+    }
+}
+
+impl From<&str> for SavePath {
+    fn from(value: &str) -> Self {
+        Self::File(value.to_owned())
+    }
+}
+
+/// in memory save of the world. Written/read by [`SavePath::Snapshot`] saves/loads.
+///
+/// Uses the same format and [`SerializeFilter`] as save files.
+#[derive(Resource, Clone, Default, Deref, DerefMut)]
+pub struct WorldSnapshot(pub Vec<u8>);
+
+/// a queued save or load.
+#[derive(Clone, Debug)]
+pub enum SaveJob {
+    Save { path: SavePath, options: SaveOptions },
+    Load { path: SavePath, options: LoadOptions },
+    /// load the first of `paths` that isn't corrupted.
+    LoadFirstIntact { paths: Vec<String>, options: LoadOptions },
+    SaveDelta { path: String, options: SaveOptions },
+    LoadDeltas { base: String, deltas: Vec<String>, options: LoadOptions },
+    CompactDeltas { base: String, deltas: Vec<String> },
+    /// re-apply the watched save. See [`crate::hot_reload`].
+    Reload { path: String },
+    ExportBundle { path: String, kind: BundleKind, options: SaveOptions },
+}
+
+/// saves/loads waiting to run, oldest first. See [`SaveWorldCommands`].
+///
+/// [`SaveWorldCommands`]: crate::traits::SaveWorldCommands
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct SaveQueue(pub VecDeque<SaveJob>);
+
+/// the save delta saves are made against, and which entity in the world is which in it.
+///
+/// Set by every world save/load to a file. See [`crate::delta`].
+#[derive(Resource, Clone, Debug)]
+pub struct DeltaBase {
+    pub path: String,
+    /// world entity -> its id in the base and its deltas.
+    pub ids: EntityHashMap<Entity>,
+    /// index the next entity that is new since the base is saved under.
+    pub next_index: u32,
+    /// change tick of the last save/load against the base.
+    pub last_save: Tick,
+    /// deltas written on top of the base, oldest first.
+    pub deltas: Vec<String>,
+    /// saved components of each entity as of the last save/load against the base, so removing one counts as a change.
+    pub components: EntityHashMap<HashSet<ComponentId>>,
 }
 
-impl GetFilePath for LoadRequest {
-    fn path(&self) -> &Path {
-        self.path.as_ref()
+impl DeltaBase {
+    pub fn new(path: String, ids: EntityHashMap<Entity>, last_save: Tick) -> Self {
+        // new entities get ids past every one in the base so they can't clash.
+        let next_index = ids.values().map(|n| n.index() + 1).max().unwrap_or(0);
+        Self {
+            path,
+            ids,
+            next_index,
+            last_save,
+            deltas: Vec::new(),
+            components: EntityHashMap::default(),
+        }
+    }
+
+    /// base for a loaded save, from the map of entities in the save to the entities they were spawned as.
+    pub fn loaded(path: String, entity_map: EntityHashMap<Entity>, last_save: Tick) -> Self {
+        let ids = entity_map.into_iter().map(|(saved, e)| (e, saved)).collect();
+        Self::new(path, ids, last_save)
     }
 }
 
@@ -87,6 +485,86 @@ pub struct ComponentsOnSave {
     pub components: HashMap<TypeId, String>,
 }
 
+/// whether each component type on [`Save`] entities makes it into saves, and if not, why.
+///
+/// Refreshed on the first save/load after types are registered, and on [`RefreshCounter`] changes. Dropped components
+/// are logged as warnings.
+///
+/// [`Save`]: moonshine_save::save::Save
+#[derive(Resource, Default, Clone, Debug)]
+pub struct SerializabilityReport {
+    pub components: Vec<ComponentSerializability>,
+}
+
+impl SerializabilityReport {
+    /// components that are left out of saves with no synonym saved in their place and no filter rule asking for it.
+    pub fn dropped(&self) -> impl Iterator<Item = &ComponentSerializability> {
+        self.components.iter().filter(|n| n.silently_dropped())
+    }
+
+    /// logs the report. Dropped components are warned about, everything else is logged at debug.
+    pub fn log(&self) {
+        for component in &self.components {
+            if component.silently_dropped() {
+                log::warn!(
+                    "{:#} on {:#} saved entities will be dropped from saves: {:#}",
+                    component.name,
+                    component.entity_count,
+                    component.reason()
+                );
+            } else {
+                log::debug!("{:#}: {:#}", component.name, component.reason());
+            }
+        }
+    }
+}
+
+/// serializability of a component type on [`Save`] entities.
+///
+/// [`Save`]: moonshine_save::save::Save
+#[derive(Clone, Debug)]
+pub struct ComponentSerializability {
+    pub name: String,
+    /// `None` for components without a rust type.
+    pub type_id: Option<TypeId>,
+    /// number of saved entities with this component.
+    pub entity_count: usize,
+    pub registered: bool,
+    pub reflect_component: bool,
+    pub reflect_serialize: bool,
+    /// denied by the [`SerializeFilter`].
+    pub denied: bool,
+    /// type path of the synonym saved in place of this component, if it is a synonym target.
+    pub synonym: Option<String>,
+}
+
+impl ComponentSerializability {
+    /// whether this component is written to saves.
+    pub fn saved(&self) -> bool {
+        self.registered && self.reflect_component && !self.denied && self.synonym.is_none()
+    }
+
+    /// whether this component is left out of saves without that being asked for.
+    pub fn silently_dropped(&self) -> bool {
+        !self.saved() && !self.denied && self.synonym.is_none()
+    }
+
+    /// why this component is/isn't saved.
+    pub fn reason(&self) -> String {
+        if let Some(synonym) = &self.synonym {
+            format!("saved as its synonym {:#}", synonym)
+        } else if self.denied {
+            "denied by the serialize filter".to_owned()
+        } else if !self.registered {
+            "not registered. Add it with `.register_type::<T>()`".to_owned()
+        } else if !self.reflect_component {
+            "missing `ReflectComponent`. Add `#[reflect(Component)]` to it".to_owned()
+        } else {
+            "saved".to_owned()
+        }
+    }
+}
+
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct ShowSerializable {
@@ -0,0 +1,288 @@
+//! json schema of synonyms as they appear in json saves, for tools that write saves without running bevy.
+//!
+//! Every type a synonym uses is described under `$defs` by type path. Types serialized through serde
+//! ([`ReflectSerialize`]) are described by their reflected shape, which matches serde derives. Glam vectors/quats are
+//! arrays of numbers.
+
+use std::any::TypeId;
+
+use bevy_reflect::{ReflectSerialize, TypeInfo, TypeRegistry, VariantInfo, serde::SerializationData};
+use bevy_synonymize::resources::SynonymTargets;
+use serde_json::{Map, Value, json};
+
+/// json schema of a save entity's components, with a property for every synonym in `synonyms` by type path.
+pub fn synonym_schema(registry: &TypeRegistry, synonyms: &SynonymTargets) -> Value {
+    let mut builder = SchemaBuilder {
+        registry,
+        defs: Map::new(),
+    };
+    let mut properties = Map::new();
+    let mut synonyms = synonyms
+        .values()
+        .filter_map(|n| registry.get_type_info(*n))
+        .collect::<Vec<_>>();
+    synonyms.sort_by_key(|n| n.type_path());
+    synonyms.dedup_by_key(|n| n.type_id());
+    for info in synonyms {
+        properties.insert(info.type_path().to_owned(), builder.schema(info));
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "synonyms",
+        "description": "synonym components of a saved entity, by type path",
+        "type": "object",
+        "properties": properties,
+        "$defs": builder.defs,
+    })
+}
+
+struct SchemaBuilder<'a> {
+    registry: &'a TypeRegistry,
+    defs: Map<String, Value>,
+}
+
+impl SchemaBuilder<'_> {
+    /// schema of a field/item of type `type_id`. `info` is used if it isn't registered.
+    fn field(&mut self, type_id: TypeId, info: Option<&'static TypeInfo>) -> Value {
+        match info.or_else(|| self.registry.get_type_info(type_id)) {
+            Some(info) => self.schema(info),
+            None => json!({ "description": "unregistered type" }),
+        }
+    }
+
+    /// named types go in `$defs` and are referred to. Everything else is inlined.
+    fn schema(&mut self, info: &TypeInfo) -> Value {
+        let named = matches!(
+            info,
+            TypeInfo::Struct(_) | TypeInfo::TupleStruct(_) | TypeInfo::Enum(_)
+        ) && !is_option(info);
+        if !named {
+            return self.describe(info);
+        }
+        let path = info.type_path().to_owned();
+        if !self.defs.contains_key(&path) {
+            // placeholder, so recursive types refer to themselves instead of recursing forever.
+            self.defs.insert(path.clone(), Value::Null);
+            let schema = self.describe(info);
+            self.defs.insert(path.clone(), schema);
+        }
+        json!({ "$ref": def_ref(&path) })
+    }
+
+    fn describe(&mut self, info: &TypeInfo) -> Value {
+        let serde = self
+            .registry
+            .get(info.type_id())
+            .is_some_and(|n| n.contains::<ReflectSerialize>());
+        if serde
+            && info.type_path().starts_with("glam::")
+            && let Some(len) = field_len(info)
+        {
+            return json!({
+                "type": "array",
+                "items": { "type": "number" },
+                "minItems": len,
+                "maxItems": len,
+            });
+        }
+        if is_option(info) {
+            return self.option(info);
+        }
+        let registry = self.registry;
+        let skipped = |index: usize| {
+            registry
+                .get(info.type_id())
+                .and_then(|n| n.data::<SerializationData>())
+                .is_some_and(|n| n.is_field_skipped(index))
+        };
+
+        match info {
+            TypeInfo::Struct(info) => {
+                let fields = info
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !skipped(*i))
+                    .map(|(_, n)| (n.name(), n.type_id(), n.type_info()))
+                    .collect::<Vec<_>>();
+                self.object(fields)
+            }
+            TypeInfo::TupleStruct(info) => {
+                let fields = info
+                    .iter()
+                    .filter(|n| !skipped(n.index()))
+                    .map(|n| (n.type_id(), n.type_info()))
+                    .collect::<Vec<_>>();
+                // newtypes are written as their inner value.
+                match (info.field_len(), fields.as_slice()) {
+                    (1, [(type_id, info)]) => self.field(*type_id, *info),
+                    _ => self.tuple(fields),
+                }
+            }
+            TypeInfo::Tuple(info) => {
+                let fields = info.iter().map(|n| (n.type_id(), n.type_info())).collect();
+                self.tuple(fields)
+            }
+            TypeInfo::List(info) => {
+                json!({ "type": "array", "items": self.field(info.item_ty().id(), info.item_info()) })
+            }
+            TypeInfo::Array(info) => json!({
+                "type": "array",
+                "items": self.field(info.item_ty().id(), info.item_info()),
+                "minItems": info.capacity(),
+                "maxItems": info.capacity(),
+            }),
+            TypeInfo::Set(info) => json!({
+                "type": "array",
+                "items": self.field(info.value_ty().id(), None),
+                "uniqueItems": true,
+            }),
+            TypeInfo::Map(info) => json!({
+                "type": "object",
+                "additionalProperties": self.field(info.value_ty().id(), info.value_info()),
+            }),
+            TypeInfo::Enum(info) => {
+                // externally tagged: unit variants are strings, others an object with the variant as the only key.
+                let variants = info
+                    .iter()
+                    .map(|variant| {
+                        let value = match variant {
+                            VariantInfo::Unit(n) => return json!({ "const": n.name() }),
+                            VariantInfo::Struct(n) => {
+                                let fields = n.iter().map(|n| (n.name(), n.type_id(), n.type_info())).collect();
+                                self.object(fields)
+                            }
+                            VariantInfo::Tuple(n) if n.field_len() == 1 => {
+                                let field = n.field_at(0).unwrap();
+                                self.field(field.type_id(), field.type_info())
+                            }
+                            VariantInfo::Tuple(n) => {
+                                let fields = n.iter().map(|n| (n.type_id(), n.type_info())).collect();
+                                self.tuple(fields)
+                            }
+                        };
+                        let mut properties = Map::new();
+                        properties.insert(variant.name().to_owned(), value);
+                        json!({
+                            "type": "object",
+                            "properties": properties,
+                            "required": [variant.name()],
+                            "additionalProperties": false,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "oneOf": variants })
+            }
+            TypeInfo::Opaque(info) => opaque(info.type_path()),
+        }
+    }
+
+    /// `null`, or the value itself.
+    fn option(&mut self, info: &TypeInfo) -> Value {
+        let some = match info {
+            TypeInfo::Enum(info) => info.variant("Some").and_then(|n| match n {
+                VariantInfo::Tuple(n) => n.field_at(0),
+                _ => None,
+            }),
+            _ => None,
+        };
+        let some = some
+            .map(|n| self.field(n.type_id(), n.type_info()))
+            .unwrap_or_else(|| json!({}));
+        json!({ "anyOf": [{ "type": "null" }, some] })
+    }
+
+    fn object(&mut self, fields: Vec<(&'static str, TypeId, Option<&'static TypeInfo>)>) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for (name, type_id, info) in fields {
+            properties.insert(name.to_owned(), self.field(type_id, info));
+            required.push(Value::from(name));
+        }
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
+
+    fn tuple(&mut self, fields: Vec<(TypeId, Option<&'static TypeInfo>)>) -> Value {
+        let len = fields.len();
+        let items = fields
+            .into_iter()
+            .map(|(type_id, info)| self.field(type_id, info))
+            .collect::<Vec<_>>();
+        json!({
+            "type": "array",
+            "prefixItems": items,
+            "minItems": len,
+            "maxItems": len,
+        })
+    }
+}
+
+/// uri fragment pointing at `$defs/<path>`. Type paths are escaped as a json pointer token(RFC 6901), then
+/// percent-encoded, as they can have `/`, `<`, spaces, etc. in them(e.g: generics, arrays).
+fn def_ref(path: &str) -> String {
+    let token = path.replace('~', "~0").replace('/', "~1");
+    let mut fragment = String::from("#/$defs/");
+    for byte in token.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => fragment.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'='
+            | b':' | b'@' => fragment.push(byte as char),
+            _ => fragment.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    fragment
+}
+
+fn is_option(info: &TypeInfo) -> bool {
+    let table = info.type_path_table();
+    matches!(info, TypeInfo::Enum(_))
+        && table.module_path() == Some("core::option")
+        && table.ident() == Some("Option")
+}
+
+fn field_len(info: &TypeInfo) -> Option<usize> {
+    match info {
+        TypeInfo::Struct(n) => Some(n.field_len()),
+        TypeInfo::TupleStruct(n) => Some(n.field_len()),
+        _ => None,
+    }
+}
+
+/// schema of types reflected as a single value, by how serde writes them.
+fn opaque(type_path: &str) -> Value {
+    match type_path {
+        "bool" => json!({ "type": "boolean" }),
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => json!({ "type": "integer", "minimum": 0 }),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => json!({ "type": "integer" }),
+        "f32" | "f64" => json!({ "type": "number" }),
+        "char" => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        "alloc::string::String"
+        | "alloc::borrow::Cow<str>"
+        | "std::path::PathBuf"
+        | "bevy_asset::path::AssetPath" => json!({ "type": "string" }),
+        "uuid::Uuid" => json!({ "type": "string", "format": "uuid" }),
+        // written as `Entity::to_bits`.
+        "bevy_ecs::entity::Entity" => json!({ "type": "integer", "minimum": 0 }),
+        _ => json!({ "description": format!("opaque type {}", type_path) }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_def_refs() {
+        assert_eq!(def_ref("glam::Vec3"), "#/$defs/glam::Vec3");
+        assert_eq!(
+            def_ref("core::option::Option<[f32; 3]>"),
+            "#/$defs/core::option::Option%3C%5Bf32;%203%5D%3E"
+        );
+        assert_eq!(def_ref("a/b~c"), "#/$defs/a~1b~0c");
+    }
+}
@@ -0,0 +1,334 @@
+//! save headers, and listing/managing save files in a folder for save menus.
+
+use std::{
+    cmp::Reverse,
+    fs,
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy_ecs::prelude::*;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    events::LoadError,
+    resources::SaveFormat,
+};
+
+/// start of the header line written before every world save.
+///
+/// The header is a single line of json, so ron saves stay valid ron(it reads as a comment).
+pub const HEADER_PREFIX: &[u8] = b"//save-header ";
+
+/// written into the [`SaveHeader`] of every world save.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct SaveMetadata {
+    /// version of the app writing saves.
+    pub app_version: String,
+    /// user defined metadata(player name, playtime, etc). Set with [`SaveMetadata::set_user`].
+    pub user: serde_json::Value,
+}
+
+impl SaveMetadata {
+    pub fn set_user<T: Serialize>(&mut self, user: &T) -> Result<(), serde_json::Error> {
+        self.user = serde_json::to_value(user)?;
+        Ok(())
+    }
+}
+
+/// small header at the start of every world save. Can be read without loading the save, see [`read_header`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SaveHeader {
+    /// seconds since the unix epoch when the save was written.
+    pub timestamp: u64,
+    /// [`SaveMetadata::app_version`] of the app that wrote the save.
+    pub app_version: String,
+    /// number of entities in the save.
+    pub entity_count: usize,
+    /// [`SaveMetadata::user`] when the save was written.
+    pub user: serde_json::Value,
+    /// length in bytes of the save after the header. `None` in saves written before headers had it.
+    #[serde(default)]
+    pub content_length: Option<usize>,
+    /// [`content_hash`] of the save after the header. `None` in saves written before headers had it.
+    #[serde(default)]
+    pub content_hash: Option<u64>,
+    /// root entity(as numbered in the save) of a prefab. `None` for world saves.
+    #[serde(default)]
+    pub prefab_root: Option<Entity>,
+}
+
+impl SaveHeader {
+    pub fn new(metadata: &SaveMetadata, entity_count: usize) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|n| n.as_secs())
+                .unwrap_or_default(),
+            app_version: metadata.app_version.clone(),
+            entity_count,
+            user: metadata.user.clone(),
+            content_length: None,
+            content_hash: None,
+            prefab_root: None,
+        }
+    }
+
+    /// the user defined metadata, as `T`. `None` if it was written as something else.
+    pub fn user<T: DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_value(self.user.clone()).ok()
+    }
+
+    /// the header line, ready to be put in front of a save.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, serde_json::Error> {
+        let mut bytes = HEADER_PREFIX.to_vec();
+        bytes.extend(serde_json::to_vec(self)?);
+        bytes.push(b'\n');
+        Ok(bytes)
+    }
+
+    /// the whole save: the header(with the length/hash of `content` filled in), followed by `content`.
+    pub fn seal(mut self, content: &[u8]) -> Result<Vec<u8>, serde_json::Error> {
+        self.content_length = Some(content.len());
+        self.content_hash = Some(content_hash(content));
+        let mut bytes = self.to_bytes()?;
+        bytes.extend_from_slice(content);
+        Ok(bytes)
+    }
+}
+
+/// stable(across runs/toolchains) FNV-1a hash for naming/checking saved content.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// splits a save into its header(if it has one, older saves don't) and the rest of the save.
+pub fn split_header(bytes: &[u8]) -> (Option<SaveHeader>, &[u8]) {
+    let Some(line) = bytes.strip_prefix(HEADER_PREFIX) else {
+        return (None, bytes);
+    };
+    let Some(end) = line.iter().position(|n| *n == b'\n') else {
+        return (None, bytes);
+    };
+    match serde_json::from_slice(&line[..end]) {
+        Ok(header) => (Some(header), &line[end + 1..]),
+        Err(err) => {
+            log::warn!("could not read save header: {:#}", err);
+            (None, &line[end + 1..])
+        }
+    }
+}
+
+/// checks a save against the length/hash in its header, returning the save after the header.
+///
+/// Saves without a header, or from before headers had a hash, can't be checked and always pass.
+pub fn verify_save(bytes: &[u8]) -> Result<&[u8], LoadError> {
+    let (header, content) = split_header(bytes);
+    let Some(header) = header else {
+        if bytes.starts_with(HEADER_PREFIX) {
+            return Err(LoadError::Corrupted("the header is unreadable".to_owned()));
+        }
+        return Ok(content);
+    };
+    if let Some(length) = header.content_length
+        && content.len() != length
+    {
+        return Err(LoadError::Corrupted(format!(
+            "expected {} bytes after the header, found {}",
+            length,
+            content.len()
+        )));
+    }
+    if header.content_hash.is_some_and(|n| n != content_hash(content)) {
+        return Err(LoadError::Corrupted("content hash doesn't match the header".to_owned()));
+    }
+    Ok(content)
+}
+
+/// [`verify_save`] for the save file at `path`.
+pub fn verify_file(path: impl AsRef<Path>) -> Result<(), LoadError> {
+    verify_save(&fs::read(path)?).map(|_| ())
+}
+
+/// reads just the header of the save at `path`.
+pub fn read_header(path: impl AsRef<Path>) -> io::Result<Option<SaveHeader>> {
+    // headers are small. Don't read through a whole binary save looking for a newline.
+    let mut reader = BufReader::new(fs::File::open(path)?).take(64 * 1024);
+    let mut line = Vec::new();
+    reader.read_until(b'\n', &mut line)?;
+    Ok(split_header(&line).0)
+}
+
+/// a save file in [`SaveSlots`].
+#[derive(Clone, Debug)]
+pub struct SaveSlot {
+    /// file stem of the save.
+    pub name: String,
+    pub path: PathBuf,
+    pub format: SaveFormat,
+    pub modified: Option<SystemTime>,
+    /// `None` for saves without a header(older saves, prefabs).
+    pub header: Option<SaveHeader>,
+}
+
+/// save files in a folder(e.g: an asset source's `saves` folder), for listing/managing saves from menus.
+///
+/// Slots are named by file stem: `slot_1.ron` is `slot_1`.
+#[derive(Clone, Debug)]
+pub struct SaveSlots {
+    pub folder: PathBuf,
+}
+
+impl SaveSlots {
+    /// file extensions recognized as saves.
+    pub const EXTENSIONS: [&str; 4] = ["ron", "scn", "bin", "json"];
+
+    pub fn new(folder: impl Into<PathBuf>) -> Self {
+        Self {
+            folder: folder.into(),
+        }
+    }
+
+    /// every save in the folder, newest first. An empty list if the folder doesn't exist yet.
+    pub fn list(&self) -> io::Result<Vec<SaveSlot>> {
+        let dir = match fs::read_dir(&self.folder) {
+            Ok(dir) => dir,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut slots = Vec::new();
+        for entry in dir {
+            let path = entry?.path();
+            if let Some(slot) = self.slot(&path) {
+                slots.push(slot);
+            }
+        }
+        slots.sort_by_key(|n| Reverse(n.modified));
+        Ok(slots)
+    }
+
+    /// the newest save that isn't corrupted(see [`verify_file`]), to fall back to when newer ones are.
+    pub fn newest_intact(&self) -> io::Result<Option<SaveSlot>> {
+        Ok(self.list()?.into_iter().find(|slot| match verify_file(&slot.path) {
+            Ok(()) => true,
+            Err(err) => {
+                log::warn!("skipping save {:#}: {:#}", slot.name, err);
+                false
+            }
+        }))
+    }
+
+    /// the save named `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<SaveSlot> {
+        Self::EXTENSIONS
+            .iter()
+            .map(|n| self.folder.join(format!("{}.{}", name, n)))
+            .find(|n| n.is_file())
+            .and_then(|n| self.slot(&n))
+    }
+
+    /// path to save `name` to in `format`. Pass this to [`SaveWorldCommands::save_world`].
+    ///
+    /// [`SaveWorldCommands::save_world`]: crate::traits::SaveWorldCommands::save_world
+    pub fn path(&self, name: &str, format: SaveFormat) -> String {
+        self.folder
+            .join(format!("{}.{}", name, format.extension()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn delete(&self, name: &str) -> io::Result<()> {
+        fs::remove_file(self.existing(name)?.path)
+    }
+
+    /// renames `from` to `to`. Fails if `to` already exists.
+    pub fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let (from, to) = self.copy_target(from, to)?;
+        fs::rename(from, to)
+    }
+
+    /// copies `from` to a new slot `to`. Fails if `to` already exists.
+    pub fn duplicate(&self, from: &str, to: &str) -> io::Result<()> {
+        let (from, to) = self.copy_target(from, to)?;
+        fs::copy(from, to).map(|_| ())
+    }
+
+    fn existing(&self, name: &str) -> io::Result<SaveSlot> {
+        self.get(name).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no save slot named {}", name))
+        })
+    }
+
+    /// paths to copy/move slot `from` to slot `to`, keeping its extension.
+    fn copy_target(&self, from: &str, to: &str) -> io::Result<(PathBuf, PathBuf)> {
+        if self.get(to).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("save slot {} already exists", to),
+            ));
+        }
+        let from = self.existing(from)?.path;
+        let mut target = self.folder.join(to);
+        if let Some(extension) = from.extension() {
+            target.set_extension(extension);
+        }
+        Ok((from, target))
+    }
+
+    fn slot(&self, path: &Path) -> Option<SaveSlot> {
+        let extension = path.extension()?.to_str()?;
+        if !path.is_file() || !Self::EXTENSIONS.contains(&extension) {
+            return None;
+        }
+        let header = read_header(path).unwrap_or_else(|err| {
+            log::warn!("could not read header of {:#?}: {:#}", path, err);
+            None
+        });
+        Some(SaveSlot {
+            name: path.file_stem()?.to_str()?.to_owned(),
+            path: path.to_owned(),
+            format: SaveFormat::detect(Some(extension), &[]),
+            modified: fs::metadata(path).and_then(|n| n.modified()).ok(),
+            header,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sealed(content: &[u8]) -> Vec<u8> {
+        SaveHeader::new(&SaveMetadata::default(), 1).seal(content).unwrap()
+    }
+
+    #[test]
+    fn verifies_sealed_save() {
+        let save = sealed(b"(entities: {})");
+        assert_eq!(verify_save(&save).unwrap(), b"(entities: {})");
+        // saves without a header can't be checked.
+        assert_eq!(verify_save(b"(entities: {})").unwrap(), b"(entities: {})");
+    }
+
+    #[test]
+    fn tampered_save_is_corrupted() {
+        let mut save = sealed(b"(entities: {})");
+        let last = save.len() - 2;
+        save[last] = b']';
+        assert!(matches!(verify_save(&save), Err(LoadError::Corrupted(_))));
+    }
+
+    #[test]
+    fn truncated_save_is_corrupted() {
+        let save = sealed(b"(entities: {})");
+        assert!(matches!(verify_save(&save[..save.len() - 1]), Err(LoadError::Corrupted(_))));
+        // cut inside of the header.
+        assert!(matches!(verify_save(&save[..HEADER_PREFIX.len() + 4]), Err(LoadError::Corrupted(_))));
+    }
+}
@@ -1,27 +1,29 @@
-use std::{any::TypeId, collections::HashMap};
+use std::{any::TypeId, collections::HashMap, fs, time::Duration};
 
-use bevy_app::App;
+use bevy_asset::{AssetEvent, AssetServer, LoadState};
 use bevy_ecs::prelude::*;
-use bevy_reflect::TypeInfo;
+use bevy_time::Time;
+use bevy_transform::prelude::*;
+use bevy_reflect::{ReflectSerialize, TypeInfo};
+use bevy_synonymize::resources::SynonymTargets;
 use moonshine_save::save::Save;
 
-use crate::resources::{ComponentsOnSave, SerializeFilter, TypeRegistryOnSave};
+use crate::{
+    autosave::{AutosaveSettings, AutosaveState},
+    bundle::{self, is_bundle},
+    chunks::{self, ChunkFocus},
+    delta,
+    events::{LoadCompleted, LoadFailed, MissingAsset, MissingAssets, SaveCompleted, SaveFailed, TriggerAutosave},
+    hot_reload::{self, SaveFileAsset, SaveHotReload, file_hash},
+    pipeline::{load_first_intact, load_world, save_world, saved_components_changed},
+    resources::{
+        ComponentSerializability, ComponentsOnSave, LoadRequest, SaveJob, SaveQueue, SaveRequest,
+        SaveDependencies, SavePath, SerializabilityReport, SerializeFilter, TypeRegistryOnSave,
+    },
+    undo::UndoHistory,
+};
 
 
-/// adds the given type to the skipped types list when serializing
-pub fn skip_serializing<SkippedType: 'static>(app: &mut App) {
-    type L = SerializeFilter;
-    let mut skip_list: Mut<'_, SerializeFilter> = app
-        .world_mut()
-        .get_resource_or_insert_with::<L>(|| L::default());
-
-    let skip_list_copy = skip_list.clone();
-    skip_list.0.components = skip_list_copy
-        .0
-        .components
-        .deny_by_id(TypeId::of::<SkippedType>());
-}
-
 pub fn update_last_saved_typedata(world: &mut World) {
     let mut enetities_to_save = world.query_filtered::<Entity, With<Save>>();
 
@@ -55,4 +57,430 @@ pub fn update_last_saved_typedata(world: &mut World) {
     world.insert_resource::<O>(O {
         components: saved_component_types,
     });
-}
\ No newline at end of file
+}
+
+/// builds a [`SerializabilityReport`] for the components on [`Save`] entities.
+pub fn serializability_report(world: &mut World) -> SerializabilityReport {
+    let mut entities_to_save = world.query_filtered::<Entity, With<Save>>();
+
+    let mut entity_counts = HashMap::new();
+    for e in entities_to_save.iter(world) {
+        for component in world.entity(e).archetype().components() {
+            *entity_counts.entry(component).or_insert(0) += 1;
+        }
+    }
+
+    let filter = world.resource::<SerializeFilter>();
+    let synonym_targets = world.get_resource::<SynonymTargets>();
+    let type_registry = world.resource::<AppTypeRegistry>().read();
+
+    let mut components = entity_counts
+        .into_iter()
+        .map(|(component, entity_count)| {
+            let comp_info = world.components().get_info(component).unwrap();
+            let type_id = comp_info.type_id();
+            let registration = type_id.and_then(|id| type_registry.get(id));
+            ComponentSerializability {
+                name: comp_info.name().to_owned(),
+                type_id,
+                entity_count,
+                registered: registration.is_some(),
+                reflect_component: registration
+                    .is_some_and(|n| n.data::<ReflectComponent>().is_some()),
+                reflect_serialize: registration
+                    .is_some_and(|n| n.data::<ReflectSerialize>().is_some()),
+                denied: type_id.is_some_and(|id| !filter.components.is_allowed_by_id(id)),
+                synonym: type_id
+                    .and_then(|id| synonym_targets?.get(&id))
+                    .map(|synonym| match type_registry.get(*synonym) {
+                        Some(registration) => registration.type_info().type_path().to_owned(),
+                        None => format!("{:?}", synonym),
+                    }),
+            }
+        })
+        .collect::<Vec<_>>();
+    components.sort_by(|a, b| a.name.cmp(&b.name));
+
+    SerializabilityReport { components }
+}
+
+/// refreshes the [`SerializabilityReport`] resource, and logs it.
+pub fn update_serializability_report(world: &mut World) {
+    let report = serializability_report(world);
+    report.log();
+    world.insert_resource(report);
+}
+
+/// whether the registered types differ from the [`TypeRegistryOnSave`].
+fn registered_types_changed(world: &World) -> bool {
+    let known = &world.resource::<TypeRegistryOnSave>().registry;
+    let registry = world.resource::<AppTypeRegistry>().read();
+    registry.iter().count() != known.len() || registry.iter().any(|n| !known.contains_key(&n.type_id()))
+}
+
+/// moves [`SaveRequest`]/[`LoadRequest`] resources into the [`SaveQueue`].
+pub fn queue_requests(world: &mut World) {
+    if let Some(SaveRequest { path, options }) = world.remove_resource::<SaveRequest>() {
+        world
+            .resource_mut::<SaveQueue>()
+            .push_back(SaveJob::Save {
+                path: path.into(),
+                options,
+            });
+    }
+    if let Some(LoadRequest { path, options }) = world.remove_resource::<LoadRequest>() {
+        world
+            .resource_mut::<SaveQueue>()
+            .push_back(SaveJob::Load {
+                path: path.into(),
+                options,
+            });
+    }
+}
+
+/// runs the oldest queued save/load, and sends an event with how it went.
+pub fn process_save_queue(world: &mut World) {
+    let Some(job) = world.resource_mut::<SaveQueue>().pop_front() else {
+        return;
+    };
+    // the report(and its warnings) only changes when types are registered. Otherwise it's rebuilt on `RefreshCounter` changes.
+    if registered_types_changed(world) {
+        update_last_saved_typedata(world);
+        update_serializability_report(world);
+    }
+
+    match job {
+        SaveJob::Save { path, options } => match save_world(world, &path, &options) {
+            Ok(completed) => {
+                world.send_event(completed);
+            }
+            Err(error) => {
+                log::warn!("could not save world to {:#}: {:#}", path, error);
+                world.send_event(SaveFailed { path, error });
+            }
+        },
+        SaveJob::Load { path, options } => match load_world(world, &path, &options) {
+            Ok(completed) => {
+                world.send_event(completed);
+            }
+            Err(error) => {
+                log::warn!("could not load world from {:#}: {:#}", path, error);
+                world.send_event(LoadFailed { path, error });
+            }
+        },
+        SaveJob::LoadFirstIntact { paths, options } => {
+            match load_first_intact(world, &paths, &options) {
+                Ok(completed) => {
+                    world.send_event(completed);
+                }
+                Err(error) => {
+                    log::warn!("could not load any of {:#?}: {:#}", paths, error);
+                    let path = paths.first().cloned().unwrap_or_default();
+                    world.send_event(LoadFailed {
+                        path: path.into(),
+                        error,
+                    });
+                }
+            }
+        }
+        SaveJob::SaveDelta { path, options } => match delta::save_delta(world, &path, &options) {
+            Ok(completed) => {
+                world.send_event(completed);
+            }
+            Err(error) => {
+                log::warn!("could not save delta to {:#}: {:#}", path, error);
+                world.send_event(SaveFailed {
+                    path: path.into(),
+                    error,
+                });
+            }
+        },
+        SaveJob::LoadDeltas {
+            base,
+            deltas,
+            options,
+        } => match delta::load_deltas(world, &base, &deltas, &options) {
+            Ok(completed) => {
+                world.send_event(completed);
+            }
+            Err(error) => {
+                log::warn!("could not load {:#} with deltas: {:#}", base, error);
+                world.send_event(LoadFailed {
+                    path: base.into(),
+                    error,
+                });
+            }
+        },
+        SaveJob::CompactDeltas { base, deltas } => {
+            match delta::compact_deltas(world, &base, &deltas) {
+                Ok(completed) => {
+                    world.send_event(completed);
+                }
+                Err(error) => {
+                    log::warn!("could not compact deltas into {:#}: {:#}", base, error);
+                    world.send_event(SaveFailed {
+                        path: base.into(),
+                        error,
+                    });
+                }
+            }
+        }
+        SaveJob::ExportBundle {
+            path,
+            kind,
+            options,
+        } => match bundle::export_bundle(world, &path, kind, &options) {
+            Ok(completed) => {
+                world.send_event(completed);
+            }
+            Err(error) => {
+                log::warn!("could not export bundle to {:#}: {:#}", path, error);
+                world.send_event(SaveFailed {
+                    path: path.into(),
+                    error,
+                });
+            }
+        },
+        SaveJob::Reload { path } => match hot_reload::reload_save(world, &path) {
+            Ok(reloaded) => {
+                world.send_event(reloaded);
+            }
+            Err(error) => {
+                log::warn!("could not reload {:#}: {:#}", path, error);
+                world.send_event(LoadFailed {
+                    path: path.into(),
+                    error,
+                });
+            }
+        },
+    }
+}
+
+/// counts down to the next autosave, and marks one as due when the interval passes or one is triggered.
+pub fn tick_autosave(
+    time: Res<Time>,
+    settings: Res<AutosaveSettings>,
+    mut state: ResMut<AutosaveState>,
+    mut triggers: EventReader<TriggerAutosave>,
+) {
+    state.elapsed += time.delta();
+    let triggered = triggers.read().count() > 0;
+    if triggered || settings.interval.is_some_and(|n| state.elapsed >= n) {
+        state.elapsed = Duration::ZERO;
+        state.due = true;
+    }
+}
+
+pub fn autosave_due(state: Res<AutosaveState>) -> bool {
+    state.due
+}
+
+/// queues a save into the next autosave slot, unless nothing saved has changed since the last autosave.
+pub fn autosave(world: &mut World) {
+    let settings = world.resource::<AutosaveSettings>().clone();
+    let last_autosave = {
+        let mut state = world.resource_mut::<AutosaveState>();
+        state.due = false;
+        state.last_autosave
+    };
+
+    let saved = world
+        .query_filtered::<Entity, With<Save>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    if let Some((tick, entity_count)) = last_autosave
+        && entity_count == saved.len()
+        && !saved_components_changed(world, &saved, tick)
+    {
+        log::debug!("nothing saved has changed since the last autosave. Skipping");
+        return;
+    }
+    if let Err(err) = fs::create_dir_all(&settings.folder) {
+        log::warn!("could not create autosave folder {:#}: {:#}", settings.folder, err);
+        return;
+    }
+
+    let change_tick = world.read_change_tick();
+    let slot = {
+        let mut state = world.resource_mut::<AutosaveState>();
+        let slot = state.next_slot % settings.slots.max(1);
+        state.next_slot = (slot + 1) % settings.slots.max(1);
+        state.last_autosave = Some((change_tick, saved.len()));
+        slot
+    };
+    // slots are overwritten as autosaves rotate, so deltas can't be made against them.
+    let mut options = settings.options.clone();
+    options.delta_base = false;
+    world.resource_mut::<SaveQueue>().push_back(SaveJob::Save {
+        path: settings.slot_path(slot).into(),
+        options,
+    });
+}
+
+/// forgets undo history when a save is loaded, as it refers to entities that were unloaded.
+pub fn reset_undo_on_load(mut loads: EventReader<LoadCompleted>, mut history: ResMut<UndoHistory>) {
+    if loads.read().count() > 0 {
+        history.reset();
+    }
+}
+
+/// keeps the [`SaveDependencies`] up to date with the asset server, and reports assets that failed to load once all are done.
+pub fn track_save_dependencies(
+    asset_server: Option<Res<AssetServer>>,
+    mut dependencies: ResMut<SaveDependencies>,
+    mut missing: EventWriter<MissingAssets>,
+) {
+    let Some(asset_server) = asset_server else {
+        return;
+    };
+    if dependencies.reported {
+        return;
+    }
+    for (path, dependency) in dependencies.assets.iter_mut() {
+        let id = match asset_server.get_path_id(path.as_str()) {
+            Some(id) => id,
+            // nothing asked for it(yet). Load it, so assets that are never loaded still settle and get reported.
+            None => dependency
+                .handle
+                .get_or_insert_with(|| asset_server.load_untyped(path.as_str()))
+                .id()
+                .untyped(),
+        };
+        dependency.state = asset_server.get_load_state(id);
+    }
+    if !dependencies.is_finished() {
+        return;
+    }
+    dependencies.reported = true;
+
+    let assets = dependencies
+        .assets
+        .iter()
+        .filter_map(|(path, dependency)| match &dependency.state {
+            Some(LoadState::Failed(err)) => Some(MissingAsset {
+                path: path.clone(),
+                entities: dependency.entities.clone(),
+                error: err.to_string(),
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !assets.is_empty() {
+        log::warn!(
+            "{:#} assets referenced by {:#?} could not be loaded: {:#?}",
+            assets.len(),
+            dependencies.save,
+            assets.iter().map(|n| &n.path).collect::<Vec<_>>()
+        );
+        missing.write(MissingAssets {
+            save: dependencies.save.clone(),
+            assets,
+        });
+    }
+}
+
+/// watches the last save loaded from a file under the [`SaveHotReload`] folder for changes.
+pub fn watch_loaded_save(
+    mut loads: EventReader<LoadCompleted>,
+    mut saves: EventReader<SaveCompleted>,
+    asset_server: Res<AssetServer>,
+    mut hot_reload: ResMut<SaveHotReload>,
+) {
+    for load in loads.read() {
+        // whatever was watched was unloaded.
+        hot_reload.path = None;
+        hot_reload.handle = None;
+        let SavePath::File(path) = &load.path else {
+            continue;
+        };
+        if is_bundle(path) {
+            continue;
+        }
+        let Some(asset_path) = hot_reload.asset_path(path) else {
+            log::warn!("{:#} is not in {:#}. Not hot reloading it", path, hot_reload.folder);
+            continue;
+        };
+        hot_reload.handle = Some(asset_server.load(asset_path));
+        hot_reload.path = Some(path.clone());
+        hot_reload.hash = file_hash(path);
+    }
+    for save in saves.read() {
+        match &save.path {
+            SavePath::File(path) if hot_reload.path.as_ref() == Some(path) => {
+                hot_reload.hash = file_hash(path);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// queues a reload of the watched save when it changes on disk. Changes from saving it in the app are skipped.
+pub fn reload_modified_save(
+    mut events: EventReader<AssetEvent<SaveFileAsset>>,
+    hot_reload: Res<SaveHotReload>,
+    mut queue: ResMut<SaveQueue>,
+) {
+    let (Some(path), Some(handle)) = (&hot_reload.path, &hot_reload.handle) else {
+        events.clear();
+        return;
+    };
+    let modified = events.read().any(|n| n.is_modified(handle));
+    if modified && file_hash(path) != hot_reload.hash {
+        queue.push_back(SaveJob::Reload { path: path.clone() });
+    }
+}
+
+/// loads/unloads chunks around [`ChunkFocus`] entities. Chunks are left as they are while there are none.
+pub fn stream_chunks_around_focus(world: &mut World) {
+    let focuses = world
+        .query_filtered::<AnyOf<(&Transform, &GlobalTransform)>, With<ChunkFocus>>()
+        .iter(world)
+        .filter_map(|(transform, global)| {
+            global
+                .map(|n| n.translation())
+                .or(transform.map(|n| n.translation))
+        })
+        .collect::<Vec<_>>();
+    if focuses.is_empty() {
+        return;
+    }
+    chunks::stream_chunks(world, &focuses);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy_app::{App, TaskPoolPlugin, Update};
+    use bevy_asset::AssetPlugin;
+
+    use super::*;
+    use crate::resources::AssetDependency;
+
+    #[test]
+    fn reports_assets_that_never_load() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .add_event::<MissingAssets>()
+            .add_systems(Update, track_save_dependencies);
+        let mut dependencies = SaveDependencies::default();
+        dependencies
+            .assets
+            .insert("does/not/exist.png".to_owned(), AssetDependency::default());
+        app.insert_resource(dependencies);
+
+        for _ in 0..200 {
+            app.update();
+            if app.world().resource::<SaveDependencies>().reported {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let events = app.world().resource::<Events<MissingAssets>>();
+        let mut missing = events.get_cursor();
+        assert!(missing
+            .read(events)
+            .flat_map(|n| &n.assets)
+            .any(|n| n.path == "does/not/exist.png"));
+    }
+}
@@ -0,0 +1,239 @@
+use bevy_ecs::prelude::*;
+use bevy_math::{IVec3, Vec3};
+use bevy_transform::prelude::*;
+
+use crate::{
+    bundle::BundleKind,
+    chunks::{self, LoadedChunks, load_chunk_with_events, save_chunk_with_events},
+    events::{LoadFailed, PrefabSpawned, SaveFailed},
+    prefab, undo,
+    resources::{LoadOptions, SaveJob, SaveOptions, SavePath, SaveQueue},
+};
+
+/// queue saves/loads from [`Commands`].
+///
+/// Requests run one per frame in the order they were made. Listen for
+/// [`SaveCompleted`]/[`SaveFailed`] and [`LoadCompleted`]/[`LoadFailed`] for the result.
+///
+/// Paths can be files, or [`SavePath::Snapshot`] to save to/load from the [`WorldSnapshot`] in memory.
+///
+/// [`WorldSnapshot`]: crate::resources::WorldSnapshot
+/// [`SaveCompleted`]: crate::events::SaveCompleted
+/// [`SaveFailed`]: crate::events::SaveFailed
+/// [`LoadCompleted`]: crate::events::LoadCompleted
+/// [`LoadFailed`]: crate::events::LoadFailed
+pub trait SaveWorldCommands {
+    /// save every [`Save`](moonshine_save::save::Save) entity to `path`.
+    fn save_world(&mut self, path: impl Into<SavePath>, options: SaveOptions);
+    /// replace the saved part of the world with the save at `path`.
+    fn load_world(&mut self, path: impl Into<SavePath>, options: LoadOptions);
+    /// like [`Self::load_world`] with the first of `paths` that isn't corrupted or missing, e.g: the newest good
+    /// autosave from [`AutosaveSettings::slot_paths_newest_first`].
+    ///
+    /// [`AutosaveSettings::slot_paths_newest_first`]: crate::autosave::AutosaveSettings::slot_paths_newest_first
+    fn load_first_intact(&mut self, paths: Vec<String>, options: LoadOptions);
+    /// save only what changed since the last save/load to a file(the [`DeltaBase`]) to `path`.
+    ///
+    /// [`DeltaBase`]: crate::resources::DeltaBase
+    fn save_delta(&mut self, path: impl Into<String>, options: SaveOptions);
+    /// like [`Self::load_world`], with `deltas`(oldest first) applied on top of the save at `base`.
+    fn load_deltas(&mut self, base: impl Into<String>, deltas: Vec<String>, options: LoadOptions);
+    /// fold `deltas` into the save at `base`, and delete them.
+    fn compact_deltas(&mut self, base: impl Into<String>, deltas: Vec<String>);
+    /// save the world along with the assets it refers to by path, so it can be loaded elsewhere. See [`crate::bundle`].
+    fn export_bundle(&mut self, path: impl Into<String>, kind: BundleKind, options: SaveOptions);
+}
+
+impl SaveWorldCommands for Commands<'_, '_> {
+    fn save_world(&mut self, path: impl Into<SavePath>, options: SaveOptions) {
+        queue_job(
+            self,
+            SaveJob::Save {
+                path: path.into(),
+                options,
+            },
+        );
+    }
+
+    fn load_world(&mut self, path: impl Into<SavePath>, options: LoadOptions) {
+        queue_job(
+            self,
+            SaveJob::Load {
+                path: path.into(),
+                options,
+            },
+        );
+    }
+
+    fn load_first_intact(&mut self, paths: Vec<String>, options: LoadOptions) {
+        queue_job(self, SaveJob::LoadFirstIntact { paths, options });
+    }
+
+    fn save_delta(&mut self, path: impl Into<String>, options: SaveOptions) {
+        queue_job(
+            self,
+            SaveJob::SaveDelta {
+                path: path.into(),
+                options,
+            },
+        );
+    }
+
+    fn load_deltas(&mut self, base: impl Into<String>, deltas: Vec<String>, options: LoadOptions) {
+        queue_job(
+            self,
+            SaveJob::LoadDeltas {
+                base: base.into(),
+                deltas,
+                options,
+            },
+        );
+    }
+
+    fn compact_deltas(&mut self, base: impl Into<String>, deltas: Vec<String>) {
+        queue_job(
+            self,
+            SaveJob::CompactDeltas {
+                base: base.into(),
+                deltas,
+            },
+        );
+    }
+
+    fn export_bundle(&mut self, path: impl Into<String>, kind: BundleKind, options: SaveOptions) {
+        queue_job(
+            self,
+            SaveJob::ExportBundle {
+                path: path.into(),
+                kind,
+                options,
+            },
+        );
+    }
+}
+
+fn queue_job(commands: &mut Commands, job: SaveJob) {
+    commands.queue(move |world: &mut World| {
+        world.get_resource_or_init::<SaveQueue>().push_back(job);
+    });
+}
+
+/// save/spawn prefabs(an entity and its descendants) from [`Commands`].
+///
+/// Unlike [`SaveWorldCommands`], these run when commands are applied rather than being queued.
+pub trait PrefabCommands {
+    /// save `root` and its descendants to `path`. Sends [`SaveCompleted`]/[`SaveFailed`].
+    ///
+    /// [`SaveCompleted`]: crate::events::SaveCompleted
+    fn save_prefab(&mut self, root: Entity, path: impl Into<String>, options: SaveOptions);
+    /// spawn a copy of the prefab at `path` with its root at `transform`. Sends [`PrefabSpawned`]/[`LoadFailed`].
+    ///
+    /// Returns the root of the copy. It is despawned again if the prefab fails to spawn.
+    fn spawn_prefab(&mut self, path: impl Into<String>, transform: Transform) -> EntityCommands<'_>;
+}
+
+impl PrefabCommands for Commands<'_, '_> {
+    fn save_prefab(&mut self, root: Entity, path: impl Into<String>, options: SaveOptions) {
+        let path = path.into();
+        self.queue(move |world: &mut World| {
+            match prefab::save_prefab(world, root, &path, &options) {
+                Ok(completed) => {
+                    world.send_event(completed);
+                }
+                Err(error) => {
+                    log::warn!("could not save prefab to {:#}: {:#}", path, error);
+                    world.send_event(SaveFailed {
+                        path: path.into(),
+                        error,
+                    });
+                }
+            }
+        });
+    }
+
+    fn spawn_prefab(&mut self, path: impl Into<String>, transform: Transform) -> EntityCommands<'_> {
+        let path = path.into();
+        let root = self.spawn_empty().id();
+        self.queue(move |world: &mut World| {
+            match prefab::spawn_prefab(world, &path, transform, Some(root)) {
+                Ok(root) => {
+                    world.send_event(PrefabSpawned { path, root });
+                }
+                Err(error) => {
+                    log::warn!("could not spawn prefab {:#}: {:#}", path, error);
+                    if let Ok(e) = world.get_entity_mut(root) {
+                        e.despawn();
+                    }
+                    world.send_event(LoadFailed {
+                        path: path.into(),
+                        error,
+                    });
+                }
+            }
+        });
+        self.entity(root)
+    }
+}
+
+/// load/save chunks of a chunked save from [`Commands`]. Requires [`ChunkedSavePlugin`]. See [`crate::chunks`].
+///
+/// Like [`PrefabCommands`], these run when commands are applied. Sends [`ChunkLoaded`]/[`ChunkSaved`], or
+/// [`LoadFailed`]/[`SaveFailed`].
+///
+/// [`ChunkedSavePlugin`]: crate::plugins::ChunkedSavePlugin
+/// [`ChunkLoaded`]: crate::events::ChunkLoaded
+/// [`ChunkSaved`]: crate::events::ChunkSaved
+pub trait ChunkCommands {
+    /// load `chunk`, if it isn't already.
+    fn load_chunk(&mut self, chunk: IVec3);
+    /// save `chunk` and despawn its entities.
+    fn unload_chunk(&mut self, chunk: IVec3);
+    /// save every loaded chunk, keeping them loaded.
+    fn save_chunks(&mut self);
+    /// load the chunks around `focus`, and save/unload every other one. For streaming without [`ChunkFocus`].
+    ///
+    /// [`ChunkFocus`]: crate::chunks::ChunkFocus
+    fn stream_chunks(&mut self, focus: Vec3);
+}
+
+impl ChunkCommands for Commands<'_, '_> {
+    fn load_chunk(&mut self, chunk: IVec3) {
+        self.queue(move |world: &mut World| load_chunk_with_events(world, chunk));
+    }
+
+    fn unload_chunk(&mut self, chunk: IVec3) {
+        self.queue(move |world: &mut World| save_chunk_with_events(world, chunk, true));
+    }
+
+    fn save_chunks(&mut self) {
+        self.queue(|world: &mut World| {
+            let mut loaded = world.resource::<LoadedChunks>().iter().copied().collect::<Vec<_>>();
+            loaded.sort_by_key(|n| n.to_array());
+            for chunk in loaded {
+                save_chunk_with_events(world, chunk, false);
+            }
+        });
+    }
+
+    fn stream_chunks(&mut self, focus: Vec3) {
+        self.queue(move |world: &mut World| chunks::stream_chunks(world, &[focus]));
+    }
+}
+
+/// undo/redo from [`Commands`]. Requires [`UndoPlugin`](crate::plugins::UndoPlugin).
+pub trait UndoCommands {
+    /// undo the last recorded entry. Does nothing if there is nothing to undo.
+    fn undo(&mut self);
+    /// redo the last undone entry. Does nothing if there is nothing to redo.
+    fn redo(&mut self);
+}
+
+impl UndoCommands for Commands<'_, '_> {
+    fn undo(&mut self) {
+        self.queue(undo::undo);
+    }
+
+    fn redo(&mut self) {
+        self.queue(undo::redo);
+    }
+}
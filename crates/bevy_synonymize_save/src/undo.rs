@@ -0,0 +1,669 @@
+//! undo/redo history of edits to synonyms on [`Save`] entities, and of [`Save`] entities being spawned/despawned.
+//!
+//! Synonyms are the editable surface, so edits are only recorded for them. Spawns/despawns keep every saved component
+//! so despawned entities can be brought back whole.
+//!
+//! To diff edits, the history keeps a copy of every synonym on every [`Save`] entity, and copies synonyms again when
+//! they change. Other saved components are only copied when an entity is despawned, or its spawn is undone. Memory
+//! grows with the number of synonyms in the world on top of [`UndoSettings::max_history`] entries.
+
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+use bevy_ecs::{
+    component::Tick, entity::EntityHashMap, prelude::*, relationship::RelationshipHookMode, world::DeferredWorld,
+};
+use bevy_reflect::{PartialReflect, TypeRegistry};
+use bevy_synonymize::resources::SynonymTargets;
+use bevy_time::{Real, Time};
+use moonshine_save::save::Save;
+
+use crate::resources::SerializeFilter;
+
+/// settings for [`UndoPlugin`]. Can be changed at runtime.
+///
+/// [`UndoPlugin`]: crate::plugins::UndoPlugin
+#[derive(Resource, Clone, Debug)]
+pub struct UndoSettings {
+    /// number of entries kept. The oldest are dropped past this.
+    pub max_history: usize,
+    /// edits to the same synonyms within this long of each other are undone as one(e.g: dragging a slider).
+    pub coalesce: Duration,
+}
+
+impl Default for UndoSettings {
+    fn default() -> Self {
+        Self {
+            max_history: 100,
+            coalesce: Duration::from_millis(500),
+        }
+    }
+}
+
+/// undo/redo stacks, and the last seen state of [`Save`] entities edits are diffed against.
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    /// oldest first.
+    pub undo: VecDeque<UndoEntry>,
+    /// most recently undone last.
+    pub redo: Vec<UndoEntry>,
+    /// synonyms of every [`Save`] entity, as last seen.
+    pub(crate) known: EntityHashMap<HashMap<TypeId, Box<dyn PartialReflect>>>,
+    /// saved components of entities despawned since the history last looked at the world.
+    pub(crate) despawned: EntityHashMap<Vec<Box<dyn PartialReflect>>>,
+    /// `None` until the history has seen the world once.
+    pub(crate) last_check: Option<Tick>,
+}
+
+impl UndoHistory {
+    /// forgets all history, e.g: after loading a save.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// a single recorded change.
+pub enum UndoChange {
+    /// a synonym on `entity` changed. `None` when the synonym wasn't on it.
+    Edit {
+        entity: Entity,
+        type_id: TypeId,
+        before: Option<Box<dyn PartialReflect>>,
+        after: Option<Box<dyn PartialReflect>>,
+    },
+    /// `entity` was spawned. `components` are the saved components it had when the spawn was last undone.
+    Spawn {
+        entity: Entity,
+        components: Vec<Box<dyn PartialReflect>>,
+    },
+    /// `entity` was despawned. `components` are the saved components it had.
+    Despawn {
+        entity: Entity,
+        components: Vec<Box<dyn PartialReflect>>,
+    },
+}
+
+/// changes undone/redone together: everything recorded in one frame, or coalesced edits.
+pub struct UndoEntry {
+    pub changes: Vec<UndoChange>,
+    /// real time of the last change in the entry.
+    pub time: Duration,
+}
+
+/// records changes to [`Save`] entities since the last time this ran into the [`UndoHistory`].
+pub fn record_changes(world: &mut World) {
+    let Some(mut history) = world.remove_resource::<UndoHistory>() else {
+        return;
+    };
+    let first_run = history.last_check.is_none();
+    let changes = collect_changes(world, &mut history);
+
+    // the first run only learns what the world looks like.
+    if !first_run && !changes.is_empty() {
+        let settings = world.get_resource::<UndoSettings>().cloned().unwrap_or_default();
+        let now = world
+            .get_resource::<Time<Real>>()
+            .map(|n| n.elapsed())
+            .unwrap_or_default();
+        history.redo.clear();
+        match history.undo.back_mut() {
+            Some(last)
+                if now.saturating_sub(last.time) <= settings.coalesce
+                    && coalesces(last, &changes) =>
+            {
+                coalesce(last, changes);
+                last.time = now;
+            }
+            _ => {
+                history.undo.push_back(UndoEntry { changes, time: now });
+                while history.undo.len() > settings.max_history {
+                    history.undo.pop_front();
+                }
+            }
+        }
+    }
+    world.insert_resource(history);
+}
+
+/// undoes the last entry in the [`UndoHistory`].
+pub fn undo(world: &mut World) {
+    step(world, true);
+}
+
+/// redoes the last undone entry in the [`UndoHistory`].
+pub fn redo(world: &mut World) {
+    step(world, false);
+}
+
+fn step(world: &mut World, undo: bool) {
+    // pending edits go in the history before stepping through it.
+    record_changes(world);
+    let Some(mut history) = world.remove_resource::<UndoHistory>() else {
+        return;
+    };
+    let entry = match undo {
+        true => history.undo.pop_back(),
+        false => history.redo.pop(),
+    };
+    if let Some(mut entry) = entry {
+        let respawned = apply_entry(world, &mut entry, undo);
+
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        for (from, to) in respawned {
+            let entries = history
+                .undo
+                .iter_mut()
+                .chain(history.redo.iter_mut())
+                .chain([&mut entry]);
+            for change in entries.flat_map(|n| n.changes.iter_mut()) {
+                map_change(change, from, to, &registry);
+            }
+        }
+        match undo {
+            true => history.redo.push(entry),
+            false => history.undo.push_back(entry),
+        }
+    }
+    // the world now matches the history. Don't record that as a new edit.
+    collect_changes(world, &mut history);
+    world.insert_resource(history);
+}
+
+/// applies the `before`(undo) or `after`(redo) side of `entry`. Returns entities that had to be respawned, and their new ids.
+fn apply_entry(world: &mut World, entry: &mut UndoEntry, undo: bool) -> Vec<(Entity, Entity)> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    // undoing a despawn and redoing a spawn both respawn. Reserve those first, so references between them are mapped.
+    let mut respawned = EntityHashMap::default();
+    for change in &entry.changes {
+        match change {
+            UndoChange::Spawn { entity, .. } if !undo => {
+                respawned.insert(*entity, world.spawn(Save).id());
+            }
+            UndoChange::Despawn { entity, .. } if undo => {
+                respawned.insert(*entity, world.spawn(Save).id());
+            }
+            _ => {}
+        }
+    }
+
+    let mut apply = |change: &mut UndoChange| match change {
+        UndoChange::Edit {
+            entity,
+            type_id,
+            before,
+            after,
+        } => {
+            let value = if undo { before } else { after };
+            let Some(reflect_component) = registry
+                .get(*type_id)
+                .and_then(|n| n.data::<ReflectComponent>())
+            else {
+                return;
+            };
+            let Ok(mut e) = world.get_entity_mut(*entity) else {
+                log::warn!("could not find {:#} to undo/redo an edit on. Skipping", entity);
+                return;
+            };
+            match value {
+                Some(value) => reflect_component.apply_or_insert_mapped(
+                    &mut e,
+                    value.as_partial_reflect(),
+                    &registry,
+                    &mut respawned,
+                    RelationshipHookMode::Run,
+                ),
+                None => reflect_component.remove(&mut e),
+            }
+        }
+        UndoChange::Spawn { entity, components } | UndoChange::Despawn { entity, components } => {
+            match respawned.get(entity).copied() {
+                Some(e) => insert_components(world, e, components, &registry, &mut respawned),
+                None => {
+                    if world.get_entity(*entity).is_ok() {
+                        // what to bring it back with on redo/undo.
+                        *components = saved_components(world, *entity, &registry);
+                        world.entity_mut(*entity).despawn();
+                    }
+                }
+            }
+        }
+    };
+    match undo {
+        true => entry.changes.iter_mut().rev().for_each(&mut apply),
+        false => entry.changes.iter_mut().for_each(&mut apply),
+    }
+    respawned.into_iter().collect()
+}
+
+fn insert_components(
+    world: &mut World,
+    entity: Entity,
+    components: &[Box<dyn PartialReflect>],
+    registry: &TypeRegistry,
+    mapper: &mut EntityHashMap<Entity>,
+) {
+    let mut e = world.entity_mut(entity);
+    for component in components {
+        let Some(reflect_component) = component
+            .get_represented_type_info()
+            .and_then(|n| registry.get(n.type_id()))
+            .and_then(|n| n.data::<ReflectComponent>())
+        else {
+            continue;
+        };
+        reflect_component.apply_or_insert_mapped(
+            &mut e,
+            component.as_partial_reflect(),
+            registry,
+            mapper,
+            RelationshipHookMode::Run,
+        );
+    }
+}
+
+/// points a change at `to` instead of `from`, including entity references in its values.
+fn map_change(change: &mut UndoChange, from: Entity, to: Entity, registry: &TypeRegistry) {
+    let (entity, values) = match change {
+        UndoChange::Edit {
+            entity,
+            before,
+            after,
+            ..
+        } => (entity, before.iter_mut().chain(after.iter_mut()).collect::<Vec<_>>()),
+        UndoChange::Spawn { entity, components } | UndoChange::Despawn { entity, components } => {
+            (entity, components.iter_mut().collect())
+        }
+    };
+    if *entity == from {
+        *entity = to;
+    }
+    for value in values {
+        let Some(reflect_component) = value
+            .get_represented_type_info()
+            .and_then(|n| registry.get(n.type_id()))
+            .and_then(|n| n.data::<ReflectComponent>())
+        else {
+            continue;
+        };
+        if let Some(value) = value.try_as_reflect_mut() {
+            reflect_component.map_entities(value, &mut (from, to));
+        }
+    }
+}
+
+/// whether `changes` only edit what `last` already edits, and can be folded into it.
+fn coalesces(last: &UndoEntry, changes: &[UndoChange]) -> bool {
+    let edited = last
+        .changes
+        .iter()
+        .map(|n| match n {
+            UndoChange::Edit {
+                entity, type_id, ..
+            } => Some((*entity, *type_id)),
+            _ => None,
+        })
+        .collect::<Option<HashSet<_>>>();
+    let Some(edited) = edited else {
+        return false;
+    };
+    changes.iter().all(|n| match n {
+        UndoChange::Edit {
+            entity, type_id, ..
+        } => edited.contains(&(*entity, *type_id)),
+        _ => false,
+    })
+}
+
+/// folds edits into `last`, keeping its `before` values.
+fn coalesce(last: &mut UndoEntry, changes: Vec<UndoChange>) {
+    for change in changes {
+        let UndoChange::Edit {
+            entity: new_entity,
+            type_id: new_type,
+            after: new_after,
+            ..
+        } = change
+        else {
+            continue;
+        };
+        for existing in last.changes.iter_mut() {
+            if let UndoChange::Edit {
+                entity,
+                type_id,
+                after,
+                ..
+            } = existing
+                && *entity == new_entity
+                && *type_id == new_type
+            {
+                *after = new_after;
+                break;
+            }
+        }
+    }
+}
+
+/// diffs synonyms on [`Save`] entities against what the history last saw, and updates it to match the world.
+fn collect_changes(world: &mut World, history: &mut UndoHistory) -> Vec<UndoChange> {
+    let saved = world
+        .query_filtered::<Entity, With<Save>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    let this_run = world.read_change_tick();
+    let filter = &world.resource::<SerializeFilter>().components;
+    let synonyms = world
+        .get_resource::<SynonymTargets>()
+        .map(|n| n.values().copied().collect::<HashSet<_>>())
+        .unwrap_or_default();
+    let registry = world.resource::<AppTypeRegistry>().read();
+
+    let mut changes = Vec::new();
+    for e in &saved {
+        let entity = world.entity(*e);
+        let new = !history.known.contains_key(e);
+        let mut known = history.known.remove(e).unwrap_or_default();
+        let mut current = HashMap::new();
+
+        for component in entity.archetype().components() {
+            let Some(type_id) = world
+                .components()
+                .get_info(component)
+                .and_then(|n| n.type_id())
+            else {
+                continue;
+            };
+            if !synonyms.contains(&type_id) || !filter.is_allowed_by_id(type_id) {
+                continue;
+            }
+            let Some(reflect_component) = registry
+                .get(type_id)
+                .and_then(|n| n.data::<ReflectComponent>())
+            else {
+                continue;
+            };
+            let changed = history.last_check.is_none_or(|since| {
+                entity
+                    .get_change_ticks_by_id(component)
+                    .is_some_and(|n| n.is_changed(since, this_run))
+            });
+            if !changed
+                && let Some(value) = known.remove(&type_id)
+            {
+                current.insert(type_id, value);
+                continue;
+            }
+            let Some(value) = reflect_component.reflect(entity) else {
+                continue;
+            };
+            let value = clone_value(value.as_partial_reflect());
+            let before = known.remove(&type_id);
+            let same = before
+                .as_ref()
+                .is_some_and(|n| n.reflect_partial_eq(value.as_ref()).unwrap_or(false));
+            if !new && !same {
+                changes.push(UndoChange::Edit {
+                    entity: *e,
+                    type_id,
+                    before,
+                    after: Some(clone_value(value.as_ref())),
+                });
+            }
+            current.insert(type_id, value);
+        }
+
+        if new {
+            // copied if the spawn is ever undone.
+            changes.push(UndoChange::Spawn {
+                entity: *e,
+                components: Vec::new(),
+            });
+        } else {
+            // whatever is left was removed from the entity.
+            for (type_id, before) in known {
+                changes.push(UndoChange::Edit {
+                    entity: *e,
+                    type_id,
+                    before: Some(before),
+                    after: None,
+                });
+            }
+        }
+        history.known.insert(*e, current);
+    }
+
+    let still_saved = saved.into_iter().collect::<HashSet<_>>();
+    let despawned = history
+        .known
+        .keys()
+        .filter(|n| !still_saved.contains(n))
+        .copied()
+        .collect::<Vec<_>>();
+    for e in despawned {
+        history.known.remove(&e);
+        match history.despawned.remove(&e) {
+            Some(components) => changes.push(UndoChange::Despawn {
+                entity: e,
+                components,
+            }),
+            None => log::warn!("{:#} stopped being saved without a snapshot of it. Can't undo that", e),
+        }
+    }
+    history.despawned.clear();
+
+    history.last_check = Some(this_run);
+    changes
+}
+
+/// copies what a [`Save`] entity the history knows has before it is despawned(or stops being saved), so undoing
+/// that brings it back whole.
+pub fn snapshot_despawned(trigger: Trigger<OnRemove, Save>, mut world: DeferredWorld) {
+    let entity = trigger.target();
+    if !world
+        .get_resource::<UndoHistory>()
+        .is_some_and(|n| n.known.contains_key(&entity))
+    {
+        return;
+    }
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let components = saved_components(&world, entity, &registry.read());
+    if let Some(mut history) = world.get_resource_mut::<UndoHistory>() {
+        history.despawned.insert(entity, components);
+    }
+}
+
+/// copies of the components of `entity` that would be saved.
+fn saved_components(world: &World, entity: Entity, registry: &TypeRegistry) -> Vec<Box<dyn PartialReflect>> {
+    let filter = &world.resource::<SerializeFilter>().components;
+    let synonym_targets = world.get_resource::<SynonymTargets>();
+    let entity = world.entity(entity);
+    entity
+        .archetype()
+        .components()
+        .filter_map(|n| world.components().get_info(n).and_then(|n| n.type_id()))
+        .filter(|n| filter.is_allowed_by_id(*n) && !synonym_targets.is_some_and(|targets| targets.contains_key(n)))
+        .filter_map(|n| registry.get(n).and_then(|n| n.data::<ReflectComponent>()))
+        .filter_map(|n| n.reflect(entity))
+        .map(|n| clone_value(n.as_partial_reflect()))
+        .collect()
+}
+
+fn clone_value(value: &dyn PartialReflect) -> Box<dyn PartialReflect> {
+    value
+        .reflect_clone()
+        .map(|n| n.into_partial_reflect())
+        .unwrap_or_else(|_| value.to_dynamic())
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::Reflect;
+
+    use super::*;
+
+    /// a synonym.
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health(u32);
+
+    /// a synonym.
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Armor(u32);
+
+    /// saved, but not a synonym.
+    #[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Label(u32);
+
+    /// stand-ins for what `Health`/`Armor` are synonyms of.
+    struct HealthTarget;
+    struct ArmorTarget;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        {
+            let registry = world.resource::<AppTypeRegistry>();
+            let mut registry = registry.write();
+            registry.register::<Health>();
+            registry.register::<Armor>();
+            registry.register::<Label>();
+        }
+        let mut targets = SynonymTargets::default();
+        targets.insert(TypeId::of::<HealthTarget>(), TypeId::of::<Health>());
+        targets.insert(TypeId::of::<ArmorTarget>(), TypeId::of::<Armor>());
+        world.insert_resource(targets);
+        world.init_resource::<SerializeFilter>();
+        world.init_resource::<UndoSettings>();
+        world.init_resource::<UndoHistory>();
+        world.init_resource::<Time<Real>>();
+        world.add_observer(snapshot_despawned);
+        world
+    }
+
+    /// a frame later(by `elapsed`) than the last one, with changes made by `edit` recorded at the end.
+    fn frame(world: &mut World, elapsed: Duration, edit: impl FnOnce(&mut World)) {
+        world.increment_change_tick();
+        world.resource_mut::<Time<Real>>().update_with_duration(elapsed);
+        edit(world);
+        record_changes(world);
+    }
+
+    const LATER: Duration = Duration::from_secs(1);
+
+    fn entries(world: &World) -> (usize, usize) {
+        let history = world.resource::<UndoHistory>();
+        (history.undo.len(), history.redo.len())
+    }
+
+    #[test]
+    fn undoes_edits() {
+        let mut world = world();
+        let e = world.spawn((Save, Health(10))).id();
+        // learns the world.
+        record_changes(&mut world);
+        frame(&mut world, LATER, |world| {
+            world.entity_mut(e).insert(Health(5));
+        });
+        assert_eq!(entries(&world), (1, 0));
+
+        undo(&mut world);
+        assert_eq!(world.get::<Health>(e), Some(&Health(10)));
+        assert_eq!(entries(&world), (0, 1));
+        redo(&mut world);
+        assert_eq!(world.get::<Health>(e), Some(&Health(5)));
+        assert_eq!(entries(&world), (1, 0));
+    }
+
+    #[test]
+    fn undoes_inserts_and_removes() {
+        let mut world = world();
+        let e = world.spawn((Save, Health(10))).id();
+        record_changes(&mut world);
+        frame(&mut world, LATER, |world| {
+            world.entity_mut(e).insert(Armor(2));
+        });
+        frame(&mut world, LATER, |world| {
+            world.entity_mut(e).remove::<Health>();
+        });
+        assert_eq!(entries(&world), (2, 0));
+
+        undo(&mut world);
+        assert_eq!(world.get::<Health>(e), Some(&Health(10)));
+        assert_eq!(world.get::<Armor>(e), Some(&Armor(2)));
+        undo(&mut world);
+        assert_eq!(world.get::<Armor>(e), None);
+        redo(&mut world);
+        assert_eq!(world.get::<Armor>(e), Some(&Armor(2)));
+        redo(&mut world);
+        assert_eq!(world.get::<Health>(e), None);
+    }
+
+    #[test]
+    fn undoes_despawns() {
+        let mut world = world();
+        let e = world.spawn((Save, Health(10), Label(1))).id();
+        record_changes(&mut world);
+        frame(&mut world, LATER, |world| {
+            world.entity_mut(e).insert(Health(5));
+        });
+        frame(&mut world, LATER, |world| {
+            world.entity_mut(e).despawn();
+        });
+        assert_eq!(entries(&world), (2, 0));
+
+        // brought back whole, as a new entity.
+        undo(&mut world);
+        let mut respawned = world.query_filtered::<Entity, With<Save>>();
+        let respawned = respawned.single(&world).unwrap();
+        assert_ne!(respawned, e);
+        assert_eq!(world.get::<Health>(respawned), Some(&Health(5)));
+        assert_eq!(world.get::<Label>(respawned), Some(&Label(1)));
+
+        // older entries point at the respawned entity.
+        undo(&mut world);
+        assert_eq!(world.get::<Health>(respawned), Some(&Health(10)));
+
+        redo(&mut world);
+        redo(&mut world);
+        assert!(world.get_entity(respawned).is_err());
+        assert_eq!(entries(&world), (2, 0));
+    }
+
+    #[test]
+    fn coalesces_edits_within_window() {
+        let mut world = world();
+        let e = world.spawn((Save, Health(10))).id();
+        record_changes(&mut world);
+        let within = world.resource::<UndoSettings>().coalesce / 2;
+        for health in [9, 8, 7] {
+            frame(&mut world, within, |world| {
+                world.entity_mut(e).insert(Health(health));
+            });
+        }
+        assert_eq!(entries(&world), (1, 0));
+        // not the same edit.
+        frame(&mut world, within, |world| {
+            world.entity_mut(e).insert(Armor(1));
+        });
+        // too late.
+        frame(&mut world, LATER, |world| {
+            world.entity_mut(e).insert(Armor(2));
+        });
+        assert_eq!(entries(&world), (3, 0));
+
+        undo(&mut world);
+        undo(&mut world);
+        undo(&mut world);
+        assert_eq!(world.get::<Health>(e), Some(&Health(10)));
+        assert_eq!(world.get::<Armor>(e), None);
+        redo(&mut world);
+        assert_eq!(world.get::<Health>(e), Some(&Health(7)));
+    }
+}